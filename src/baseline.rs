@@ -0,0 +1,502 @@
+//! Saved summaries (`--save-baseline`) and checks of new data against them
+//! (`--check-baseline`).
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::Path;
+
+use crate::sampling::SampleSummary;
+use crate::{ColumnStats, ColumnSummary, ParquetSummary, SCHEMA_VERSION};
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Baseline {
+  schema_version: u32,
+  file: String,
+  filter: Option<String>,
+  sample: Option<SampleSummary>,
+  n_rows: usize,
+  columns: Vec<ColumnSummary>,
+}
+
+/// How far the current data may move away from the baseline before a check
+/// fails.
+pub struct Tolerances {
+  /// Relative change in row count, in percent
+  pub row_count: f64,
+  /// Absolute change in null rate, in percentage points
+  pub null_rate: f64,
+  /// Relative change in mean, standard deviation, quartiles, distinct
+  /// counts, and boolean true rates, in percent
+  pub statistic: f64,
+  pub allow_new_columns: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BaselineCheck {
+  pub baseline: String,
+  pub passed: bool,
+  pub violations: Vec<Violation>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Violation {
+  /// `None` for dataset-level checks such as the row count
+  pub column: Option<String>,
+  pub check: String,
+  pub message: String,
+}
+
+pub fn save_baseline(summary: &ParquetSummary, path: &Path) -> Result<()> {
+  let baseline = Baseline {
+    schema_version: SCHEMA_VERSION,
+    file: summary.file.clone(),
+    filter: summary.filter.clone(),
+    sample: summary.sample.clone(),
+    n_rows: summary.n_rows,
+    columns: summary.columns.clone(),
+  };
+
+  let file = File::create(path)
+    .with_context(|| format!("Failed to create baseline file '{}'", path.display()))?;
+  serde_json::to_writer_pretty(BufWriter::new(file), &baseline)
+    .with_context(|| format!("Failed to write baseline file '{}'", path.display()))
+}
+
+fn load_baseline(path: &Path) -> Result<Baseline> {
+  let file = File::open(path)
+    .with_context(|| format!("Failed to open baseline file '{}'", path.display()))?;
+  let baseline: Baseline = serde_json::from_reader(BufReader::new(file))
+    .with_context(|| format!("Failed to read baseline file '{}'", path.display()))?;
+
+  if baseline.schema_version != SCHEMA_VERSION {
+    anyhow::bail!(
+      "Baseline '{}' has schema version {}, expected {}; save it again with this version",
+      path.display(),
+      baseline.schema_version,
+      SCHEMA_VERSION
+    );
+  }
+  Ok(baseline)
+}
+
+pub fn check_baseline(
+  summary: &ParquetSummary,
+  path: &Path,
+  tolerances: &Tolerances,
+) -> Result<BaselineCheck> {
+  let baseline = load_baseline(path)?;
+  let mut violations = Vec::new();
+
+  let mut violation = |column: Option<&str>, check: &str, message: String| {
+    violations.push(Violation {
+      column: column.map(str::to_string),
+      check: check.to_string(),
+      message,
+    });
+  };
+
+  // Statistics of differently filtered or sampled rows are not comparable
+  if baseline.filter != summary.filter {
+    let describe = |filter: &Option<String>| match filter {
+      Some(filter) => format!("'{filter}'"),
+      None => "none".to_string(),
+    };
+    violation(
+      None,
+      "filter",
+      format!(
+        "Filter {} → {}",
+        describe(&baseline.filter),
+        describe(&summary.filter)
+      ),
+    );
+  }
+  let drawn =
+    |sample: &Option<SampleSummary>| sample.as_ref().map(|sample| (sample.sample, sample.seed));
+  if drawn(&baseline.sample) != drawn(&summary.sample) {
+    let describe = |sample: &Option<SampleSummary>| match sample {
+      Some(sample) => format!("{} (seed {})", sample.sample, sample.seed),
+      None => "all rows".to_string(),
+    };
+    violation(
+      None,
+      "sample",
+      format!(
+        "Sample {} → {}",
+        describe(&baseline.sample),
+        describe(&summary.sample)
+      ),
+    );
+  }
+
+  if let Some(change) = Change::between(baseline.n_rows as f64, summary.n_rows as f64)
+    && change.exceeds(tolerances.row_count)
+  {
+    violation(
+      None,
+      "row_count",
+      format!(
+        "Row count {} → {} ({change}, tolerance {}%)",
+        baseline.n_rows, summary.n_rows, tolerances.row_count
+      ),
+    );
+  }
+
+  for old in &baseline.columns {
+    let Some(new) = summary
+      .columns
+      .iter()
+      .find(|column| column.name == old.name)
+    else {
+      violation(
+        Some(&old.name),
+        "missing_column",
+        format!("Column '{}' ({}) is missing", old.name, old.data_type),
+      );
+      continue;
+    };
+
+    if old.data_type != new.data_type {
+      violation(
+        Some(&old.name),
+        "data_type",
+        format!(
+          "Column '{}' changed type: {} → {}",
+          old.name, old.data_type, new.data_type
+        ),
+      );
+      continue;
+    }
+
+    if let (Some(old_rate), Some(new_rate)) = (old.null_percentage, new.null_percentage)
+      && (new_rate - old_rate).abs() > tolerances.null_rate
+    {
+      violation(
+        Some(&old.name),
+        "null_rate",
+        format!(
+          "Column '{}' null rate {old_rate:.1}% → {new_rate:.1}% ({:+.1} pp, tolerance {} pp)",
+          old.name,
+          new_rate - old_rate,
+          tolerances.null_rate
+        ),
+      );
+    }
+
+    for (check, old_value, new_value) in compared_statistics(&old.summary, &new.summary) {
+      // NaN round-trips through JSON as null, so treat it as missing on both sides
+      let old_value = old_value.filter(|value| !value.is_nan());
+      let new_value = new_value.filter(|value| !value.is_nan());
+
+      let change = match (old_value, new_value) {
+        (Some(old_value), Some(new_value)) => Change::between(old_value, new_value),
+        _ => None,
+      };
+      let exceeded = match &change {
+        Some(change) => change.exceeds(tolerances.statistic),
+        None => old_value.is_some() != new_value.is_some(),
+      };
+      if exceeded {
+        let change = change.map_or_else(String::new, |change| format!("{change}, "));
+        violation(
+          Some(&old.name),
+          check,
+          format!(
+            "Column '{}' {check} {} → {} ({change}tolerance {}%)",
+            old.name,
+            format_value(old_value),
+            format_value(new_value),
+            tolerances.statistic
+          ),
+        );
+      }
+    }
+  }
+
+  if !tolerances.allow_new_columns {
+    for new in &summary.columns {
+      if !baseline
+        .columns
+        .iter()
+        .any(|column| column.name == new.name)
+      {
+        violation(
+          Some(&new.name),
+          "new_column",
+          format!("Column '{}' ({}) is new", new.name, new.data_type),
+        );
+      }
+    }
+  }
+
+  Ok(BaselineCheck {
+    baseline: path.display().to_string(),
+    passed: violations.is_empty(),
+    violations,
+  })
+}
+
+/// Change of a statistic from its baseline value.
+#[derive(Debug, PartialEq)]
+enum Change {
+  /// Fraction of the baseline value
+  Relative(f64),
+  /// Away from a zero baseline, relative to which any change is infinite
+  FromZero(f64),
+}
+
+impl Change {
+  fn between(old: f64, new: f64) -> Option<Change> {
+    let change = if old == 0.0 {
+      Change::FromZero(new)
+    } else {
+      Change::Relative((new - old) / old.abs())
+    };
+    match change {
+      Change::Relative(change) | Change::FromZero(change) if !change.is_finite() => None,
+      change => Some(change),
+    }
+  }
+
+  /// Whether the change is beyond a tolerance in percent. Any move away from
+  /// a zero baseline is, since no percentage describes it.
+  fn exceeds(&self, tolerance: f64) -> bool {
+    match self {
+      Change::Relative(change) => change.abs() * 100.0 > tolerance,
+      Change::FromZero(change) => *change != 0.0,
+    }
+  }
+}
+
+impl std::fmt::Display for Change {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Change::Relative(change) => write!(f, "{:+.1}%", change * 100.0),
+      Change::FromZero(_) => write!(f, "from zero"),
+    }
+  }
+}
+
+/// Pairs of statistics held to the relative tolerance, by check name.
+fn compared_statistics(
+  old: &ColumnStats,
+  new: &ColumnStats,
+) -> Vec<(&'static str, Option<f64>, Option<f64>)> {
+  match (old, new) {
+    (
+      ColumnStats::Numerical {
+        mean: old_mean,
+        std_dev: old_std_dev,
+        median: old_median,
+        q25: old_q25,
+        q75: old_q75,
+        ..
+      },
+      ColumnStats::Numerical {
+        mean,
+        std_dev,
+        median,
+        q25,
+        q75,
+        ..
+      },
+    ) => vec![
+      ("mean", *old_mean, *mean),
+      ("std_dev", *old_std_dev, *std_dev),
+      ("median", *old_median, *median),
+      ("q25", *old_q25, *q25),
+      ("q75", *old_q75, *q75),
+    ],
+    (
+      ColumnStats::Categorical {
+        total_unique: old_unique,
+        ..
+      },
+      ColumnStats::Categorical { total_unique, .. },
+    ) => vec![(
+      "distinct_count",
+      Some(*old_unique as f64),
+      Some(*total_unique as f64),
+    )],
+    (
+      ColumnStats::Boolean {
+        true_percentage: old_true,
+        ..
+      },
+      ColumnStats::Boolean {
+        true_percentage, ..
+      },
+    ) => vec![("true_percentage", *old_true, *true_percentage)],
+    _ => vec![],
+  }
+}
+
+fn format_value(value: Option<f64>) -> String {
+  match value {
+    // Distinct counts
+    Some(value) if value.fract() == 0.0 && value.abs() < 1e15 => format!("{value:.0}"),
+    Some(value) => format!("{value:.6}"),
+    None => "N/A".to_string(),
+  }
+}
+
+pub fn format_check(check: &BaselineCheck) -> String {
+  let mut output = String::new();
+
+  if check.passed {
+    output.push_str(&format!("🧪 Baseline Check: PASSED ({})\n", check.baseline));
+  } else {
+    output.push_str(&format!(
+      "🧪 Baseline Check: FAILED with {} violation(s) ({})\n",
+      check.violations.len(),
+      check.baseline
+    ));
+  }
+  for violation in &check.violations {
+    output.push_str(&format!("   ❌ {}\n", violation.message));
+  }
+
+  output.push('\n');
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Profiler;
+  use crate::sampling::Sample;
+  use crate::test_support::TempDir;
+  use polars::prelude::*;
+
+  fn tolerances() -> Tolerances {
+    Tolerances {
+      row_count: 10.0,
+      null_rate: 5.0,
+      statistic: 10.0,
+      allow_new_columns: false,
+    }
+  }
+
+  /// Saves a baseline of `old` and checks `new` against it.
+  fn check(old: &DataFrame, new: &DataFrame) -> BaselineCheck {
+    let profiler = Profiler::new();
+    check_summaries(
+      &profiler.profile_frame(old).unwrap(),
+      &profiler.profile_frame(new).unwrap(),
+    )
+  }
+
+  fn check_summaries(old: &ParquetSummary, new: &ParquetSummary) -> BaselineCheck {
+    let root = TempDir::new("baseline");
+    let path = root.join("baseline.json");
+    save_baseline(old, &path).unwrap();
+    check_baseline(new, &path, &tolerances()).unwrap()
+  }
+
+  #[test]
+  fn measures_changes_relative_to_the_baseline() {
+    assert_eq!(Change::between(200.0, 250.0), Some(Change::Relative(0.25)));
+    assert_eq!(Change::between(-4.0, -2.0), Some(Change::Relative(0.5)));
+    assert_eq!(Change::between(0.0, 3.0), Some(Change::FromZero(3.0)));
+    assert_eq!(Change::between(1.0, f64::INFINITY), None);
+
+    assert!(Change::Relative(0.11).exceeds(10.0));
+    assert!(!Change::Relative(-0.09).exceeds(10.0));
+    assert!(Change::FromZero(1e-9).exceeds(10.0));
+    assert!(!Change::FromZero(0.0).exceeds(10.0));
+
+    assert_eq!(Change::Relative(-0.125).to_string(), "-12.5%");
+    assert_eq!(Change::FromZero(0.05).to_string(), "from zero");
+  }
+
+  #[test]
+  fn flags_any_move_away_from_a_zero_baseline() {
+    // A relative tolerance of 10% would have let 0 → 0.05 pass as 5%
+    let old = df!("value" => [0.0, 0.0, 0.0, 0.0]).unwrap();
+    let new = df!("value" => [0.0, 0.0, 0.1, 0.1]).unwrap();
+    let result = check(&old, &new);
+
+    assert!(!result.passed);
+    let mean = result
+      .violations
+      .iter()
+      .find(|violation| violation.check == "mean")
+      .unwrap();
+    assert_eq!(
+      mean.message,
+      "Column 'value' mean 0 → 0.050000 (from zero, tolerance 10%)"
+    );
+
+    assert!(check(&old, &old).passed);
+  }
+
+  #[test]
+  fn reports_schema_row_count_and_statistic_violations() {
+    let old = df!("id" => [1i64, 2, 3, 4], "gone" => [true, false, true, true]).unwrap();
+    let new = df!("id" => [10i64, 20, 30, 40, 50], "extra" => ["a", "b", "c", "d", "e"]).unwrap();
+    let check = check(&old, &new);
+
+    let checks = check
+      .violations
+      .iter()
+      .map(|violation| violation.check.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(
+      checks,
+      [
+        "row_count",
+        "mean",
+        "std_dev",
+        "median",
+        "q25",
+        "q75",
+        "missing_column",
+        "new_column"
+      ]
+    );
+    assert_eq!(
+      check.violations[0].message,
+      "Row count 4 → 5 (+25.0%, tolerance 10%)"
+    );
+  }
+
+  #[test]
+  fn flags_a_different_filter_or_sample() {
+    let frame = df!("value" => [1.0, 2.0, 3.0, 4.0]).unwrap();
+    let profile = |profiler: Profiler| profiler.profile_frame(&frame).unwrap();
+    let sampled = |seed| Profiler::new().sample(Sample::Fraction(1.0), seed);
+    let checks = |check: BaselineCheck| {
+      check
+        .violations
+        .into_iter()
+        .map(|violation| (violation.check, violation.message))
+        .collect::<Vec<_>>()
+    };
+
+    let check = check_summaries(
+      &profile(Profiler::new().filter("value > 0")),
+      &profile(Profiler::new()),
+    );
+    assert_eq!(
+      checks(check),
+      [(
+        "filter".to_string(),
+        "Filter 'value > 0' → none".to_string()
+      )]
+    );
+
+    let check = check_summaries(&profile(sampled(1)), &profile(sampled(2)));
+    assert_eq!(
+      checks(check),
+      [(
+        "sample".to_string(),
+        "Sample random rows with probability 1 (seed 1) → random rows with probability 1 (seed 2)"
+          .to_string()
+      )]
+    );
+
+    assert!(check_summaries(&profile(sampled(1)), &profile(sampled(1))).passed);
+  }
+}
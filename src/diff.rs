@@ -0,0 +1,1088 @@
+//! Schema and distribution drift between two inputs (`diff` subcommand).
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::{
+  COUNT_COLUMN, ColumnStats, ColumnSummary, ParquetSummary, Profiler, SCHEMA_VERSION,
+  collect_streaming, kll, value_counts,
+};
+
+/// Bins used for the population stability index: baseline deciles.
+const PSI_BINS: usize = 10;
+
+/// Bins used to approximate the Kolmogorov–Smirnov statistic: baseline
+/// percentiles. D is measured at the bin edges only, so it is a lower bound
+/// of the exact statistic, tight to about one percentile.
+const KS_BINS: usize = 100;
+
+/// Number of appeared or disappeared categories listed by name.
+const MAX_LISTED_CATEGORIES: usize = 10;
+
+#[derive(Debug, Serialize)]
+pub struct DiffReport {
+  pub schema_version: u32,
+  pub baseline: String,
+  pub current: String,
+  pub baseline_rows: usize,
+  pub current_rows: usize,
+  pub added_columns: Vec<ColumnType>,
+  pub removed_columns: Vec<ColumnType>,
+  pub retyped_columns: Vec<RetypedColumn>,
+  pub columns: Vec<ColumnDiff>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnType {
+  pub name: String,
+  pub data_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RetypedColumn {
+  pub name: String,
+  pub baseline_type: String,
+  pub current_type: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NumericalDrift {
+  pub min: StatChange,
+  pub max: StatChange,
+  pub mean: StatChange,
+  pub std_dev: StatChange,
+  pub median: StatChange,
+  pub q25: StatChange,
+  pub q75: StatChange,
+  /// Population stability index over baseline deciles
+  pub psi: Option<f64>,
+  /// Two-sample Kolmogorov–Smirnov statistic, measured at baseline percentiles
+  pub ks_statistic: Option<f64>,
+  pub ks_p_value: Option<f64>,
+}
+
+/// Drift of one column present in both inputs.
+#[derive(Debug, Serialize)]
+pub struct ColumnDiff {
+  pub name: String,
+  pub data_type: String,
+  pub null_percentage: StatChange,
+  pub drift: Drift,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StatChange {
+  pub baseline: Option<f64>,
+  pub current: Option<f64>,
+  pub change: Option<f64>,
+  /// `change` as a fraction of the baseline value
+  pub relative_change: Option<f64>,
+}
+
+impl StatChange {
+  fn new(baseline: Option<f64>, current: Option<f64>) -> Self {
+    let change = match (baseline, current) {
+      (Some(baseline), Some(current)) => Some(current - baseline),
+      _ => None,
+    };
+    StatChange {
+      baseline,
+      current,
+      change,
+      relative_change: match (change, baseline) {
+        (Some(change), Some(baseline)) if baseline != 0.0 => {
+          Some(change / baseline.abs()).filter(|relative| relative.is_finite())
+        }
+        _ => None,
+      },
+    }
+  }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Drift {
+  Numerical(Box<NumericalDrift>),
+  Categorical {
+    baseline_unique: usize,
+    current_unique: usize,
+    /// Categories only in the current input, most frequent first
+    appeared: Vec<String>,
+    appeared_count: usize,
+    /// Categories only in the baseline input, most frequent first
+    disappeared: Vec<String>,
+    disappeared_count: usize,
+    chi_square: Option<f64>,
+    degrees_of_freedom: Option<usize>,
+    chi_square_p_value: Option<f64>,
+    /// Jensen–Shannon divergence of the category frequencies, in bits (0–1)
+    jensen_shannon: Option<f64>,
+  },
+  Temporal {
+    baseline_earliest: Option<String>,
+    current_earliest: Option<String>,
+    baseline_latest: Option<String>,
+    current_latest: Option<String>,
+  },
+  /// Columns whose kind changed between the inputs, or that carry no
+  /// comparable statistics
+  Other,
+}
+
+pub fn diff_parquet(baseline: &Path, current: &Path, profiler: &Profiler) -> Result<DiffReport> {
+  if profiler.group_by.is_some() || profiler.metadata_only {
+    anyhow::bail!("diff does not support --group-by or --metadata-only");
+  }
+
+  let baseline_summary = profiler
+    .profile_path(baseline)
+    .with_context(|| format!("Failed to analyze baseline '{}'", baseline.display()))?;
+  let current_summary = profiler
+    .profile_path(current)
+    .with_context(|| format!("Failed to analyze '{}'", current.display()))?;
+  let baseline_frame = profiler.scan(baseline)?;
+  let current_frame = profiler.scan(current)?;
+
+  let find = |summary: &'_ ParquetSummary, name: &str| -> Option<usize> {
+    summary
+      .columns
+      .iter()
+      .position(|column| column.name == name)
+  };
+
+  let added_columns = current_summary
+    .columns
+    .iter()
+    .filter(|column| find(&baseline_summary, &column.name).is_none())
+    .map(column_type)
+    .collect();
+  let removed_columns = baseline_summary
+    .columns
+    .iter()
+    .filter(|column| find(&current_summary, &column.name).is_none())
+    .map(column_type)
+    .collect();
+
+  // Numerical columns are bucketed together, in one pass over each input
+  let numerical = baseline_summary
+    .columns
+    .iter()
+    .filter(|old| {
+      find(&current_summary, &old.name).is_some_and(|index| {
+        let new = &current_summary.columns[index];
+        old.data_type == new.data_type
+          && matches!(
+            (&old.summary, &new.summary),
+            (ColumnStats::Numerical { .. }, ColumnStats::Numerical { .. })
+          )
+      })
+    })
+    .map(|column| column.name.as_str())
+    .collect::<Vec<_>>();
+  let cuts = baseline_cuts(&baseline_frame, &numerical, profiler)?;
+  let baseline_buckets = bucket_counts(&baseline_frame, &numerical, &cuts)?;
+  let current_buckets = bucket_counts(&current_frame, &numerical, &cuts)?;
+  let buckets = numerical
+    .into_iter()
+    .zip(baseline_buckets.into_iter().zip(current_buckets))
+    .collect::<HashMap<_, _>>();
+
+  let mut retyped_columns = Vec::new();
+  let mut columns = Vec::new();
+  for old in &baseline_summary.columns {
+    let Some(index) = find(&current_summary, &old.name) else {
+      continue;
+    };
+    let new = &current_summary.columns[index];
+
+    if old.data_type != new.data_type {
+      retyped_columns.push(RetypedColumn {
+        name: old.name.clone(),
+        baseline_type: old.data_type.clone(),
+        current_type: new.data_type.clone(),
+      });
+    }
+
+    let drift = if old.data_type == new.data_type {
+      column_drift(
+        &baseline_frame,
+        &current_frame,
+        old,
+        new,
+        buckets.get(old.name.as_str()),
+      )
+      .with_context(|| format!("Failed to compare column '{}'", old.name))?
+    } else {
+      Drift::Other
+    };
+
+    columns.push(ColumnDiff {
+      name: old.name.clone(),
+      data_type: new.data_type.clone(),
+      null_percentage: StatChange::new(old.null_percentage, new.null_percentage),
+      drift,
+    });
+  }
+
+  Ok(DiffReport {
+    schema_version: SCHEMA_VERSION,
+    baseline: baseline_summary.file,
+    current: current_summary.file,
+    baseline_rows: baseline_summary.n_rows,
+    current_rows: current_summary.n_rows,
+    added_columns,
+    removed_columns,
+    retyped_columns,
+    columns,
+  })
+}
+
+fn column_type(column: &ColumnSummary) -> ColumnType {
+  ColumnType {
+    name: column.name.clone(),
+    data_type: column.data_type.clone(),
+  }
+}
+
+fn column_drift(
+  baseline_frame: &LazyFrame,
+  current_frame: &LazyFrame,
+  old: &ColumnSummary,
+  new: &ColumnSummary,
+  buckets: Option<&(Buckets, Buckets)>,
+) -> Result<Drift> {
+  Ok(match (&old.summary, &new.summary) {
+    (
+      ColumnStats::Numerical {
+        min: old_min,
+        max: old_max,
+        mean: old_mean,
+        std_dev: old_std_dev,
+        median: old_median,
+        q25: old_q25,
+        q75: old_q75,
+        ..
+      },
+      ColumnStats::Numerical {
+        min,
+        max,
+        mean,
+        std_dev,
+        median,
+        q25,
+        q75,
+        ..
+      },
+    ) => {
+      let psi = buckets
+        .and_then(|(expected, actual)| population_stability_index(&expected.psi, &actual.psi));
+      let (ks_statistic, ks_p_value) =
+        match buckets.and_then(|(expected, actual)| kolmogorov_smirnov(&expected.ks, &actual.ks)) {
+          Some((statistic, p_value)) => (Some(statistic), Some(p_value)),
+          None => (None, None),
+        };
+
+      Drift::Numerical(Box::new(NumericalDrift {
+        min: StatChange::new(*old_min, *min),
+        max: StatChange::new(*old_max, *max),
+        mean: StatChange::new(*old_mean, *mean),
+        std_dev: StatChange::new(*old_std_dev, *std_dev),
+        median: StatChange::new(*old_median, *median),
+        q25: StatChange::new(*old_q25, *q25),
+        q75: StatChange::new(*old_q75, *q75),
+        psi,
+        ks_statistic,
+        ks_p_value,
+      }))
+    }
+
+    // Booleans are compared as two categories, counted in their summaries
+    (ColumnStats::Categorical { .. }, ColumnStats::Categorical { .. })
+    | (ColumnStats::Boolean { .. }, ColumnStats::Boolean { .. }) => {
+      let baseline_counts = category_counts(baseline_frame, old)?;
+      let current_counts = category_counts(current_frame, new)?;
+
+      let appeared = only_in(&current_counts, &baseline_counts);
+      let disappeared = only_in(&baseline_counts, &current_counts);
+
+      let (chi_square, degrees_of_freedom, chi_square_p_value) =
+        match chi_square_test(&baseline_counts, &current_counts) {
+          Some((statistic, df, p_value)) => (Some(statistic), Some(df), Some(p_value)),
+          None => (None, None, None),
+        };
+
+      Drift::Categorical {
+        baseline_unique: baseline_counts.len(),
+        current_unique: current_counts.len(),
+        appeared_count: appeared.len(),
+        appeared: appeared.into_iter().take(MAX_LISTED_CATEGORIES).collect(),
+        disappeared_count: disappeared.len(),
+        disappeared: disappeared
+          .into_iter()
+          .take(MAX_LISTED_CATEGORIES)
+          .collect(),
+        chi_square,
+        degrees_of_freedom,
+        chi_square_p_value,
+        jensen_shannon: jensen_shannon(&baseline_counts, &current_counts),
+      }
+    }
+
+    (
+      ColumnStats::Temporal {
+        earliest: baseline_earliest,
+        latest: baseline_latest,
+        ..
+      },
+      ColumnStats::Temporal {
+        earliest, latest, ..
+      },
+    ) => Drift::Temporal {
+      baseline_earliest: baseline_earliest.clone(),
+      current_earliest: earliest.clone(),
+      baseline_latest: baseline_latest.clone(),
+      current_latest: latest.clone(),
+    },
+
+    _ => Drift::Other,
+  })
+}
+
+/// Baseline quantiles of a numerical column, at which both inputs are
+/// bucketed.
+struct Cuts {
+  psi: Vec<f64>,
+  ks: Vec<f64>,
+}
+
+/// Counts of one input in the buckets between the cuts of a column.
+struct Buckets {
+  psi: Vec<u64>,
+  ks: Vec<u64>,
+}
+
+/// The finite values of a column as `Float64`.
+fn finite_values(name: &str) -> Expr {
+  let value = col(name).cast(DataType::Float64);
+  value.clone().filter(value.is_finite())
+}
+
+/// Interior quantiles splitting each column into `PSI_BINS` and `KS_BINS`
+/// bins, deduplicated, all from one pass over the baseline.
+fn baseline_cuts(lazy_frame: &LazyFrame, names: &[&str], profiler: &Profiler) -> Result<Vec<Cuts>> {
+  if names.is_empty() {
+    return Ok(vec![]);
+  }
+  let cuts = |n_bins: usize, quantile: &dyn Fn(usize) -> Option<f64>| {
+    let mut cuts = (1..n_bins).filter_map(quantile).collect::<Vec<_>>();
+    cuts.dedup();
+    cuts
+  };
+
+  if let Some(k) = profiler.quantile_sketch {
+    let sketches = kll::sketch_columns(lazy_frame, names, k)?;
+    return Ok(
+      sketches
+        .iter()
+        .map(|sketch| Cuts {
+          psi: cuts(PSI_BINS, &|i| sketch.quantile(i as f64 / PSI_BINS as f64)),
+          ks: cuts(KS_BINS, &|i| sketch.quantile(i as f64 / KS_BINS as f64)),
+        })
+        .collect(),
+    );
+  }
+
+  let mut exprs = Vec::new();
+  for (c, name) in names.iter().enumerate() {
+    let value = finite_values(name);
+    for n_bins in [PSI_BINS, KS_BINS] {
+      exprs.extend((1..n_bins).map(|i| {
+        value
+          .clone()
+          .quantile(lit(i as f64 / n_bins as f64), profiler.quantile_method)
+          .alias(format!("{c}:{n_bins}:{i}"))
+      }));
+    }
+  }
+  let quantiles = collect_streaming(lazy_frame.clone().select(exprs))
+    .with_context(|| "Failed to compute baseline quantiles")?;
+
+  Ok(
+    (0..names.len())
+      .map(|c| {
+        let quantile = |n_bins: usize| {
+          let quantiles = &quantiles;
+          move |i: usize| {
+            let value = quantiles
+              .column(&format!("{c}:{n_bins}:{i}"))
+              .ok()?
+              .get(0)
+              .ok()?;
+            value.extract::<f64>()
+          }
+        };
+        Cuts {
+          psi: cuts(PSI_BINS, &quantile(PSI_BINS)),
+          ks: cuts(KS_BINS, &quantile(KS_BINS)),
+        }
+      })
+      .collect(),
+  )
+}
+
+/// Buckets the finite values of every column at its cuts, all in one pass.
+fn bucket_counts(lazy_frame: &LazyFrame, names: &[&str], cuts: &[Cuts]) -> Result<Vec<Buckets>> {
+  if names.is_empty() {
+    return Ok(vec![]);
+  }
+
+  let mut exprs = Vec::new();
+  for (c, (name, cuts)) in names.iter().zip(cuts).enumerate() {
+    let value = finite_values(name);
+    exprs.extend(bucket_exprs(&value, &cuts.psi, &format!("{c}:psi")));
+    exprs.extend(bucket_exprs(&value, &cuts.ks, &format!("{c}:ks")));
+  }
+  let counts = collect_streaming(lazy_frame.clone().select(exprs))
+    .with_context(|| "Failed to bucket values")?;
+
+  let read = |prefix: String, n_buckets: usize| -> Result<Vec<u64>> {
+    (0..n_buckets)
+      .map(|i| {
+        let value = counts.column(&format!("{prefix}:{i}"))?.get(0)?;
+        Ok(value.extract::<u64>().unwrap_or(0))
+      })
+      .collect()
+  };
+  cuts
+    .iter()
+    .enumerate()
+    .map(|(c, cuts)| {
+      Ok(Buckets {
+        psi: read(format!("{c}:psi"), cuts.psi.len() + 1)?,
+        ks: read(format!("{c}:ks"), cuts.ks.len() + 1)?,
+      })
+    })
+    .collect()
+}
+
+/// Counts values below the first cut, between consecutive cuts, and from the
+/// last cut up, so every finite value lands in exactly one of
+/// `cuts.len() + 1` buckets.
+fn bucket_exprs(value: &Expr, cuts: &[f64], prefix: &str) -> Vec<Expr> {
+  (0..=cuts.len())
+    .map(|i| {
+      let lower = (i > 0).then(|| value.clone().gt_eq(lit(cuts[i - 1])));
+      let upper = (i < cuts.len()).then(|| value.clone().lt(lit(cuts[i])));
+      let in_bucket = match (lower, upper) {
+        (Some(lower), Some(upper)) => lower.and(upper),
+        (Some(bound), None) | (None, Some(bound)) => bound,
+        (None, None) => value.clone().is_not_null(),
+      };
+      in_bucket.sum().alias(format!("{prefix}:{i}"))
+    })
+    .collect()
+}
+
+fn population_stability_index(expected: &[u64], actual: &[u64]) -> Option<f64> {
+  // Empty buckets are floored so a single new or vanished bucket yields a
+  // large but finite index
+  const FLOOR: f64 = 1e-4;
+
+  let expected_total = expected.iter().sum::<u64>() as f64;
+  let actual_total = actual.iter().sum::<u64>() as f64;
+  if expected_total == 0.0 || actual_total == 0.0 {
+    return None;
+  }
+
+  Some(
+    expected
+      .iter()
+      .zip(actual)
+      .map(|(e, a)| {
+        let e = (*e as f64 / expected_total).max(FLOOR);
+        let a = (*a as f64 / actual_total).max(FLOOR);
+        (a - e) * (a / e).ln()
+      })
+      .sum(),
+  )
+}
+
+/// Returns the largest gap between the two empirical CDFs at the bucket
+/// boundaries, with its asymptotic p-value.
+fn kolmogorov_smirnov(expected: &[u64], actual: &[u64]) -> Option<(f64, f64)> {
+  let expected_total = expected.iter().sum::<u64>() as f64;
+  let actual_total = actual.iter().sum::<u64>() as f64;
+  if expected_total == 0.0 || actual_total == 0.0 {
+    return None;
+  }
+
+  let mut expected_cdf = 0.0;
+  let mut actual_cdf = 0.0;
+  let mut statistic: f64 = 0.0;
+  for (e, a) in expected.iter().zip(actual) {
+    expected_cdf += *e as f64 / expected_total;
+    actual_cdf += *a as f64 / actual_total;
+    statistic = statistic.max((expected_cdf - actual_cdf).abs());
+  }
+
+  let n = expected_total * actual_total / (expected_total + actual_total);
+  let lambda = (n.sqrt() + 0.12 + 0.11 / n.sqrt()) * statistic;
+  Some((statistic, kolmogorov_q(lambda)))
+}
+
+/// Survival function of the Kolmogorov distribution.
+fn kolmogorov_q(lambda: f64) -> f64 {
+  if lambda < 1e-3 {
+    return 1.0;
+  }
+  let mut sum = 0.0;
+  let mut sign = 1.0;
+  for j in 1..=100 {
+    let term = sign * (-2.0 * (j * j) as f64 * lambda * lambda).exp();
+    sum += term;
+    if term.abs() < 1e-12 {
+      break;
+    }
+    sign = -sign;
+  }
+  (2.0 * sum).clamp(0.0, 1.0)
+}
+
+/// Counts of every non-null value, keyed by its display form. Booleans take
+/// theirs from the summary; other columns need a frequency count of their own,
+/// since summaries keep only the most frequent values.
+fn category_counts(lazy_frame: &LazyFrame, column: &ColumnSummary) -> Result<HashMap<String, u64>> {
+  if let ColumnStats::Boolean {
+    true_count,
+    false_count,
+    ..
+  } = column.summary
+  {
+    return Ok(
+      [("true", true_count), ("false", false_count)]
+        .into_iter()
+        .filter(|(_, count)| *count > 0)
+        .map(|(label, count)| (label.to_string(), count))
+        .collect(),
+    );
+  }
+
+  let name = column.name.as_str();
+  let counts = value_counts(lazy_frame, name)?;
+  let values = counts.column(name)?.as_materialized_series();
+  let value_counts = counts.column(COUNT_COLUMN)?.cast(&DataType::UInt64)?;
+
+  let mut categories = HashMap::with_capacity(counts.height());
+  for (i, count) in value_counts.u64()?.into_iter().enumerate() {
+    let value = values.get(i)?;
+    let label = match value.get_str() {
+      Some(s) => s.to_string(),
+      None => format!("{value}"),
+    };
+    categories.insert(label, count.unwrap_or(0));
+  }
+  Ok(categories)
+}
+
+/// Categories of `counts` missing from `other`, most frequent first.
+fn only_in(counts: &HashMap<String, u64>, other: &HashMap<String, u64>) -> Vec<String> {
+  let mut only = counts
+    .iter()
+    .filter(|(category, _)| !other.contains_key(*category))
+    .collect::<Vec<_>>();
+  only.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+  only
+    .into_iter()
+    .map(|(category, _)| category.clone())
+    .collect()
+}
+
+/// Pearson's chi-square test of homogeneity on the 2 × k table of category
+/// counts, returning the statistic, degrees of freedom, and p-value.
+fn chi_square_test(
+  baseline: &HashMap<String, u64>,
+  current: &HashMap<String, u64>,
+) -> Option<(f64, usize, f64)> {
+  let baseline_total = baseline.values().sum::<u64>() as f64;
+  let current_total = current.values().sum::<u64>() as f64;
+  let total = baseline_total + current_total;
+
+  let mut categories = baseline.keys().collect::<Vec<_>>();
+  categories.extend(current.keys().filter(|key| !baseline.contains_key(*key)));
+  if categories.len() < 2 || baseline_total == 0.0 || current_total == 0.0 {
+    return None;
+  }
+
+  let statistic = categories
+    .iter()
+    .map(|category| {
+      let observed_baseline = baseline.get(*category).copied().unwrap_or(0) as f64;
+      let observed_current = current.get(*category).copied().unwrap_or(0) as f64;
+      let category_total = observed_baseline + observed_current;
+      let expected_baseline = baseline_total * category_total / total;
+      let expected_current = current_total * category_total / total;
+      (observed_baseline - expected_baseline).powi(2) / expected_baseline
+        + (observed_current - expected_current).powi(2) / expected_current
+    })
+    .sum::<f64>();
+
+  let degrees_of_freedom = categories.len() - 1;
+  let p_value = upper_incomplete_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0);
+  Some((statistic, degrees_of_freedom, p_value))
+}
+
+fn jensen_shannon(baseline: &HashMap<String, u64>, current: &HashMap<String, u64>) -> Option<f64> {
+  let baseline_total = baseline.values().sum::<u64>() as f64;
+  let current_total = current.values().sum::<u64>() as f64;
+  if baseline_total == 0.0 || current_total == 0.0 {
+    return None;
+  }
+
+  // Relative entropy of `p` against the mixture, over the categories of `p`
+  let divergence = |p: &HashMap<String, u64>, p_total: f64| -> f64 {
+    p.iter()
+      .filter(|(_, count)| **count > 0)
+      .map(|(category, count)| {
+        let p_i = *count as f64 / p_total;
+        let baseline_i = baseline.get(category).copied().unwrap_or(0) as f64 / baseline_total;
+        let current_i = current.get(category).copied().unwrap_or(0) as f64 / current_total;
+        let m_i = (baseline_i + current_i) / 2.0;
+        p_i * (p_i / m_i).log2()
+      })
+      .sum()
+  };
+
+  let value = 0.5 * divergence(baseline, baseline_total) + 0.5 * divergence(current, current_total);
+  Some(value.clamp(0.0, 1.0))
+}
+
+/// Regularized upper incomplete gamma function Q(a, x), the survival function
+/// of the chi-square distribution with `2a` degrees of freedom at `2x`.
+fn upper_incomplete_gamma(a: f64, x: f64) -> f64 {
+  const EPSILON: f64 = 1e-14;
+  const MAX_ITERATIONS: usize = 1000;
+
+  if x <= 0.0 {
+    return 1.0;
+  }
+  let log_prefix = a * x.ln() - x - ln_gamma(a);
+
+  if x < a + 1.0 {
+    // Series expansion of the lower function P(a, x)
+    let mut term = 1.0 / a;
+    let mut sum = term;
+    for n in 1..MAX_ITERATIONS {
+      term *= x / (a + n as f64);
+      sum += term;
+      if term.abs() < sum.abs() * EPSILON {
+        break;
+      }
+    }
+    (1.0 - sum * log_prefix.exp()).clamp(0.0, 1.0)
+  } else {
+    // Continued fraction for Q(a, x), by the modified Lentz method
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut h = d;
+    for i in 1..MAX_ITERATIONS {
+      let an = -(i as f64) * (i as f64 - a);
+      b += 2.0;
+      d = an * d + b;
+      if d.abs() < tiny {
+        d = tiny;
+      }
+      c = b + an / c;
+      if c.abs() < tiny {
+        c = tiny;
+      }
+      d = 1.0 / d;
+      let delta = d * c;
+      h *= delta;
+      if (delta - 1.0).abs() < EPSILON {
+        break;
+      }
+    }
+    (log_prefix.exp() * h).clamp(0.0, 1.0)
+  }
+}
+
+/// Natural logarithm of the gamma function (Lanczos approximation).
+fn ln_gamma(x: f64) -> f64 {
+  const COEFFICIENTS: [f64; 6] = [
+    76.180_091_729_471_46,
+    -86.505_320_329_416_77,
+    24.014_098_240_830_91,
+    -1.231_739_572_450_155,
+    0.001_208_650_973_866_179,
+    -0.000_005_395_239_384_953,
+  ];
+
+  let tmp = x + 5.5;
+  let tmp = tmp - (x + 0.5) * tmp.ln();
+  let mut series = 1.000_000_000_190_015;
+  for (i, coefficient) in COEFFICIENTS.iter().enumerate() {
+    series += coefficient / (x + 1.0 + i as f64);
+  }
+  -tmp + (2.506_628_274_631_000_5 * series / x).ln()
+}
+
+fn format_change(change: &StatChange) -> String {
+  let value = |value: Option<f64>| match value {
+    Some(value) => format!("{value:.6}"),
+    None => "N/A".to_string(),
+  };
+  let mut text = format!("{} → {}", value(change.baseline), value(change.current));
+  if let Some(relative_change) = change.relative_change {
+    text.push_str(&format!(" ({:+.1}%)", relative_change * 100.0));
+  }
+  text
+}
+
+/// Conventional reading of the population stability index.
+fn psi_label(psi: f64) -> &'static str {
+  if psi < 0.1 {
+    "stable"
+  } else if psi < 0.25 {
+    "moderate shift"
+  } else {
+    "significant shift"
+  }
+}
+
+pub fn format_diff(report: &DiffReport) -> String {
+  let mut output = String::new();
+
+  output.push_str("🔀 Parquet Diff\n");
+  output.push_str("━━━━━━━━━━━━━━━\n");
+  output.push_str(&format!(
+    "📁 Baseline: {} ({} rows)\n",
+    report.baseline, report.baseline_rows
+  ));
+  output.push_str(&format!(
+    "📁 Current: {} ({} rows)\n",
+    report.current, report.current_rows
+  ));
+  let row_change = report.current_rows as i64 - report.baseline_rows as i64;
+  output.push_str(&format!("📏 Row change: {row_change:+}"));
+  if report.baseline_rows > 0 {
+    output.push_str(&format!(
+      " ({:+.1}%)",
+      row_change as f64 / report.baseline_rows as f64 * 100.0
+    ));
+  }
+  output.push_str("\n\n");
+
+  output.push_str("🧬 Schema Changes\n");
+  if report.added_columns.is_empty()
+    && report.removed_columns.is_empty()
+    && report.retyped_columns.is_empty()
+  {
+    output.push_str("   None\n");
+  }
+  for column in &report.added_columns {
+    output.push_str(&format!(
+      "   ➕ Added: '{}' ({})\n",
+      column.name, column.data_type
+    ));
+  }
+  for column in &report.removed_columns {
+    output.push_str(&format!(
+      "   ➖ Removed: '{}' ({})\n",
+      column.name, column.data_type
+    ));
+  }
+  for column in &report.retyped_columns {
+    output.push_str(&format!(
+      "   🔁 Retyped: '{}' ({} → {})\n",
+      column.name, column.baseline_type, column.current_type
+    ));
+  }
+  output.push('\n');
+
+  output.push_str("📋 Column Drift\n");
+  output.push_str("━━━━━━━━━━━━━━━\n\n");
+
+  for (i, column) in report.columns.iter().enumerate() {
+    output.push_str(&format!(
+      "{}. Column: '{}' ({})\n",
+      i + 1,
+      column.name,
+      column.data_type
+    ));
+
+    let nulls = &column.null_percentage;
+    match (nulls.baseline, nulls.current, nulls.change) {
+      (Some(baseline), Some(current), Some(change)) => output.push_str(&format!(
+        "   Null rate: {baseline:.1}% → {current:.1}% ({change:+.1} pp)\n"
+      )),
+      _ => output.push_str("   Null rate: N/A\n"),
+    }
+
+    match &column.drift {
+      Drift::Numerical(drift) => {
+        let NumericalDrift {
+          min,
+          max,
+          mean,
+          std_dev,
+          median,
+          q25,
+          q75,
+          psi,
+          ks_statistic,
+          ks_p_value,
+        } = drift.as_ref();
+        output.push_str("   📈 Numerical Drift:\n");
+        for (label, change) in [
+          ("Min", min),
+          ("Max", max),
+          ("Mean", mean),
+          ("Std Dev", std_dev),
+          ("Median", median),
+          ("Q1 (25%)", q25),
+          ("Q3 (75%)", q75),
+        ] {
+          output.push_str(&format!("      {label}: {}\n", format_change(change)));
+        }
+        if let Some(psi) = psi {
+          output.push_str(&format!("      PSI: {psi:.4} ({})\n", psi_label(*psi)));
+        }
+        if let (Some(statistic), Some(p_value)) = (ks_statistic, ks_p_value) {
+          output.push_str(&format!("      KS: D = {statistic:.4}, p = {p_value:.4}\n"));
+        }
+      }
+
+      Drift::Categorical {
+        baseline_unique,
+        current_unique,
+        appeared,
+        appeared_count,
+        disappeared,
+        disappeared_count,
+        chi_square,
+        degrees_of_freedom,
+        chi_square_p_value,
+        jensen_shannon,
+      } => {
+        output.push_str("   📊 Categorical Drift:\n");
+        output.push_str(&format!(
+          "      Unique: {baseline_unique} → {current_unique}\n"
+        ));
+        for (label, categories, count) in [
+          ("Appeared", appeared, appeared_count),
+          ("Disappeared", disappeared, disappeared_count),
+        ] {
+          if *count > 0 {
+            let listed = categories
+              .iter()
+              .map(|category| format!("'{category}'"))
+              .collect::<Vec<_>>()
+              .join(", ");
+            let more = count - categories.len();
+            if more > 0 {
+              output.push_str(&format!(
+                "      {label}: {count} ({listed}, +{more} more)\n"
+              ));
+            } else {
+              output.push_str(&format!("      {label}: {count} ({listed})\n"));
+            }
+          }
+        }
+        if let (Some(statistic), Some(df), Some(p_value)) =
+          (chi_square, degrees_of_freedom, chi_square_p_value)
+        {
+          output.push_str(&format!(
+            "      Chi-square: {statistic:.4} (df {df}), p = {p_value:.4}\n"
+          ));
+        }
+        if let Some(jensen_shannon) = jensen_shannon {
+          output.push_str(&format!("      Jensen–Shannon: {jensen_shannon:.4}\n"));
+        }
+      }
+
+      Drift::Temporal {
+        baseline_earliest,
+        current_earliest,
+        baseline_latest,
+        current_latest,
+      } => {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "N/A".to_string());
+        output.push_str("   🕒 Temporal Drift:\n");
+        output.push_str(&format!(
+          "      Earliest: {} → {}\n",
+          value(baseline_earliest),
+          value(current_earliest)
+        ));
+        output.push_str(&format!(
+          "      Latest: {} → {}\n",
+          value(baseline_latest),
+          value(current_latest)
+        ));
+      }
+
+      Drift::Other => {}
+    }
+
+    output.push('\n');
+  }
+
+  output.push_str("✅ Diff complete!\n");
+
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+    assert!(
+      (actual - expected).abs() <= tolerance,
+      "{actual} is not within {tolerance} of {expected}"
+    );
+  }
+
+  fn categories(counts: &[(&str, u64)]) -> HashMap<String, u64> {
+    counts
+      .iter()
+      .map(|(category, count)| (category.to_string(), *count))
+      .collect()
+  }
+
+  #[test]
+  fn kolmogorov_q_matches_critical_values() {
+    assert_close(kolmogorov_q(1.0), 0.269_999_67, 1e-7);
+    assert_close(kolmogorov_q(1.2238), 0.10, 1e-4);
+    assert_close(kolmogorov_q(1.3581), 0.05, 1e-4);
+    assert_close(kolmogorov_q(1.6276), 0.01, 1e-4);
+    assert_eq!(kolmogorov_q(0.0), 1.0);
+  }
+
+  #[test]
+  fn kolmogorov_smirnov_scores_a_known_gap() {
+    // Two samples of 100 whose CDFs differ by 0.2 after the first bucket
+    let (statistic, p_value) = kolmogorov_smirnov(&[100, 0], &[80, 20]).unwrap();
+    assert_close(statistic, 0.2, 1e-12);
+    assert_close(p_value, 0.031_376_65, 1e-6);
+
+    let (statistic, p_value) = kolmogorov_smirnov(&[10, 20, 30], &[10, 20, 30]).unwrap();
+    assert_eq!((statistic, p_value), (0.0, 1.0));
+    assert!(kolmogorov_smirnov(&[0, 0], &[1, 2]).is_none());
+  }
+
+  #[test]
+  fn ln_gamma_matches_known_values() {
+    assert_close(ln_gamma(0.5), std::f64::consts::PI.sqrt().ln(), 1e-10);
+    assert_close(ln_gamma(1.0), 0.0, 1e-10);
+    assert_close(ln_gamma(3.7), 1.428_072_326_665_388_3, 1e-10);
+    assert_close(ln_gamma(10.0), 362_880f64.ln(), 1e-10);
+  }
+
+  #[test]
+  fn chi_square_survival_matches_critical_values() {
+    // Q(k / 2, x / 2) is the chance of a chi-square of k degrees over x
+    let survival = |x: f64, k: f64| upper_incomplete_gamma(k / 2.0, x / 2.0);
+    assert_close(survival(3.841_459, 1.0), 0.05, 1e-6);
+    assert_close(survival(2.705_543, 1.0), 0.10, 1e-6);
+    assert_close(survival(5.991_465, 2.0), 0.05, 1e-6);
+    assert_close(survival(1.0, 2.0), (-0.5f64).exp(), 1e-12);
+    assert_close(survival(18.307_038, 10.0), 0.05, 1e-6);
+    assert_close(survival(124.342, 100.0), 0.05, 1e-5);
+    assert_eq!(survival(0.0, 3.0), 1.0);
+  }
+
+  #[test]
+  fn chi_square_test_scores_a_known_table() {
+    let baseline = categories(&[("a", 10), ("b", 20)]);
+    let current = categories(&[("a", 20), ("b", 10)]);
+    let (statistic, degrees_of_freedom, p_value) = chi_square_test(&baseline, &current).unwrap();
+    assert_close(statistic, 20.0 / 3.0, 1e-12);
+    assert_eq!(degrees_of_freedom, 1);
+    assert_close(p_value, 0.009_823_274_5, 1e-8);
+
+    assert!(chi_square_test(&categories(&[("a", 1)]), &categories(&[("a", 2)])).is_none());
+  }
+
+  #[test]
+  fn jensen_shannon_spans_identical_to_disjoint() {
+    let baseline = categories(&[("a", 3), ("b", 5)]);
+    let scaled = categories(&[("a", 30), ("b", 50)]);
+    assert_close(jensen_shannon(&baseline, &scaled).unwrap(), 0.0, 1e-12);
+
+    // Disjoint inputs diverge by ln 2 nats, which is one bit
+    let disjoint = categories(&[("c", 4), ("d", 1)]);
+    assert_close(jensen_shannon(&baseline, &disjoint).unwrap(), 1.0, 1e-12);
+
+    assert!(jensen_shannon(&baseline, &HashMap::new()).is_none());
+  }
+
+  #[test]
+  fn population_stability_index_floors_empty_bins() {
+    assert_close(
+      population_stability_index(&[10, 20, 30], &[20, 40, 60]).unwrap(),
+      0.0,
+      1e-12,
+    );
+
+    // A bucket that empties and one that fills score large but finite
+    let psi = population_stability_index(&[50, 50, 0], &[50, 0, 50]).unwrap();
+    assert_close(psi, 8.515_489_752_777_954, 1e-9);
+
+    assert!(population_stability_index(&[0, 0], &[1, 1]).is_none());
+  }
+
+  #[test]
+  fn scores_every_column_in_one_pass_per_input() {
+    let root = TempDir::new("diff");
+    let write = |name: &str, shift: f64, every: usize| {
+      let path = root.join(format!("{name}.parquet"));
+      let mut frame = df!(
+        "stable" => (0..1000).map(|i| f64::from(i % 100)).collect::<Vec<_>>(),
+        "shifted" => (0..1000).map(|i| f64::from(i) / 10.0 + shift).collect::<Vec<_>>(),
+        "active" => (0..1000).map(|i| i % every == 0).collect::<Vec<_>>(),
+      )
+      .unwrap();
+      ParquetWriter::new(std::fs::File::create(&path).unwrap())
+        .finish(&mut frame)
+        .unwrap();
+      path
+    };
+    let baseline = write("baseline", 0.0, 2);
+    let current = write("current", 50.0, 4);
+
+    let reports = [Profiler::new(), Profiler::new().approx_quantiles(200)]
+      .map(|profiler| diff_parquet(&baseline, &current, &profiler).unwrap());
+
+    for report in reports {
+      let drift = |name: &str| {
+        &report
+          .columns
+          .iter()
+          .find(|c| c.name == name)
+          .unwrap()
+          .drift
+      };
+
+      let Drift::Numerical(stable) = drift("stable") else {
+        panic!("expected numerical drift");
+      };
+      assert_eq!((stable.psi, stable.ks_statistic), (Some(0.0), Some(0.0)));
+      let Drift::Numerical(shifted) = drift("shifted") else {
+        panic!("expected numerical drift");
+      };
+      // Half of the current values lie past every baseline cut
+      assert!(shifted.psi.unwrap() > 1.0, "{:?}", shifted.psi);
+      assert_close(shifted.ks_statistic.unwrap(), 0.5, 0.02);
+      let Drift::Categorical { chi_square, .. } = drift("active") else {
+        panic!("expected categorical drift");
+      };
+      assert!(chi_square.unwrap() > 0.0);
+    }
+  }
+}
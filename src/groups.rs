@@ -0,0 +1,514 @@
+//! Column summaries split by the values of a key column (`--group-by`).
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::Serialize;
+
+use crate::{
+  COUNT_COLUMN, ColumnStats, ColumnSummary, Profiler, collect_streaming, project, statistics_exprs,
+  summarize_from_stats, value_counts,
+};
+
+#[derive(Debug, Serialize)]
+pub struct GroupedSummary {
+  pub key: String,
+  /// Number of distinct key values, including null, before `--max-groups`
+  pub total_groups: usize,
+  /// The largest groups, in descending order of row count
+  pub groups: Vec<GroupSummary>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct GroupSummary {
+  /// The key value, or `None` for the group of null keys
+  pub value: Option<String>,
+  pub n_rows: usize,
+  pub columns: Vec<ColumnSummary>,
+}
+
+/// Finds the largest groups of `key` and profiles them. Their aggregates
+/// come from one grouped pass, but frequency tables, sketches, histograms,
+/// and the other passes of a full profile still scan each group in turn.
+pub(crate) fn summarize_groups(
+  lazy_frame: &LazyFrame,
+  key: &str,
+  key_type: &DataType,
+  columns: &[PlSmallStr],
+  profiler: &Profiler,
+) -> Result<GroupedSummary> {
+  let counts = value_counts(lazy_frame, key)
+    .with_context(|| format!("Failed to find the groups of '{key}'"))?;
+  let keys = counts.column(key)?.as_materialized_series();
+  let key_counts = counts.column(COUNT_COLUMN)?.cast(&DataType::UInt64)?;
+  let key_counts = key_counts.u64()?;
+
+  // Categorical values compare most reliably through their string form
+  let categorical = matches!(key_type, DataType::Categorical(_, _) | DataType::Enum(_, _));
+  let key_expr = if categorical {
+    col(key).cast(DataType::String)
+  } else {
+    col(key)
+  };
+
+  // A `None` value stands for the null group
+  let mut groups: Vec<(Option<String>, Option<AnyValue<'static>>, u64)> = Vec::new();
+  for (i, count) in key_counts.into_iter().enumerate() {
+    let value = keys.get(i)?.into_static();
+    groups.push((Some(label(&value)), Some(value), count.unwrap_or(0)));
+  }
+
+  let null_count = collect_streaming(lazy_frame.clone().select([col(key).null_count()]))
+    .with_context(|| format!("Failed to count nulls in '{key}'"))?
+    .column(key)?
+    .get(0)?
+    .extract::<u64>()
+    .unwrap_or(0);
+  if null_count > 0 {
+    groups.push((None, None, null_count));
+  }
+
+  // Ties go by value, with the null group last
+  groups.sort_by(|a, b| {
+    b.2
+      .cmp(&a.2)
+      .then_with(|| a.0.is_none().cmp(&b.0.is_none()))
+      .then_with(|| a.0.cmp(&b.0))
+  });
+  let total_groups = groups.len();
+  groups.truncate(profiler.max_groups);
+
+  let schema = project(lazy_frame, columns)
+    .collect_schema()
+    .with_context(|| "Failed to read parquet schema")?;
+  let exprs = statistics_exprs(&schema, profiler);
+
+  // Null keys are aggregated on their own, as the streaming group-by can
+  // fail on them (see `value_counts`)
+  let values = groups
+    .iter()
+    .filter_map(|(label, value, _)| match value {
+      Some(_) if categorical => label
+        .clone()
+        .map(|label| AnyValue::StringOwned(label.into())),
+      value => value.clone(),
+    })
+    .collect::<Vec<_>>();
+  let values = Series::from_any_values(key.into(), &values, false)?;
+  let grouped = lazy_frame
+    .clone()
+    .filter(key_expr.clone().is_in(lit(values).implode(), false))
+    .group_by([key_expr.clone().alias(key)])
+    .agg(exprs.clone());
+  let grouped = collect_streaming(grouped)
+    .with_context(|| format!("Failed to compute column statistics by '{key}'"))?;
+  let grouped_keys = grouped.column(key)?.as_materialized_series().clone();
+
+  let groups = groups
+    .into_iter()
+    .map(|(label, value, _)| {
+      let (predicate, stats) = match &label {
+        None => {
+          let predicate = col(key).is_null();
+          let stats = collect_streaming(
+            lazy_frame
+              .clone()
+              .filter(predicate.clone())
+              .select(exprs.clone()),
+          )
+          .with_context(|| format!("Failed to compute column statistics of null '{key}'"))?;
+          (predicate, stats)
+        }
+        Some(label) => {
+          let predicate = if categorical {
+            key_expr.clone().eq(lit(label.clone()))
+          } else {
+            col(key).eq(lit(Scalar::new(key_type.clone(), value.unwrap())))
+          };
+          let row = (0..grouped_keys.len())
+            .find(|&row| {
+              grouped_keys
+                .get(row)
+                .is_ok_and(|key| self::label(&key) == *label)
+            })
+            .with_context(|| format!("Missing statistics of group {key} = {label}"))?;
+          (predicate, grouped.slice(row as i64, 1))
+        }
+      };
+
+      let (n_rows, columns) = summarize_from_stats(
+        &project(&lazy_frame.clone().filter(predicate), columns),
+        &schema,
+        &stats,
+        profiler,
+      )
+      .with_context(|| {
+        format!(
+          "Failed to summarize group {key} = {}",
+          label.as_deref().unwrap_or("null")
+        )
+      })?;
+
+      Ok(GroupSummary {
+        value: label,
+        n_rows,
+        columns,
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  Ok(GroupedSummary {
+    key: key.to_string(),
+    total_groups,
+    groups,
+  })
+}
+
+/// A key value as shown in the table header.
+fn label(value: &AnyValue) -> String {
+  match value.get_str() {
+    Some(s) => s.to_string(),
+    None => format!("{value}"),
+  }
+}
+
+/// Header of the group of null keys, which no key value can be confused
+/// with in the table.
+const NULL_GROUP: &str = "∅";
+
+/// The headline statistics of a column, as `(label, value)` pairs for one
+/// cell each in the side-by-side table.
+fn stat_cells(column: &ColumnSummary) -> Vec<(&'static str, String)> {
+  let mut cells = vec![(
+    "Nulls",
+    match (column.null_count, column.null_percentage) {
+      (Some(null_count), Some(percentage)) => format!("{null_count} ({percentage:.1}%)"),
+      (Some(null_count), None) => null_count.to_string(),
+      _ => "N/A".to_string(),
+    },
+  )];
+
+  let number = |value: Option<f64>| match value {
+    Some(value) => format!("{value:.4}"),
+    None => "N/A".to_string(),
+  };
+  let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "N/A".to_string());
+
+  match &column.summary {
+    ColumnStats::Numerical {
+      min,
+      max,
+      mean,
+      std_dev,
+      median,
+      q25,
+      q75,
+      decimal,
+      ..
+    } => cells.extend([
+      (
+        "Min",
+        decimal
+          .as_ref()
+          .and_then(|decimal| decimal.min.clone())
+          .unwrap_or_else(|| number(*min)),
+      ),
+      (
+        "Max",
+        decimal
+          .as_ref()
+          .and_then(|decimal| decimal.max.clone())
+          .unwrap_or_else(|| number(*max)),
+      ),
+      ("Mean", number(*mean)),
+      ("Std Dev", number(*std_dev)),
+      ("Median", number(*median)),
+      ("Q1 (25%)", number(*q25)),
+      ("Q3 (75%)", number(*q75)),
+    ]),
+    ColumnStats::Categorical {
+      frequency_table,
+      total_unique,
+      distinct_error,
+      frequency_error,
+      strings,
+      ..
+    } => {
+      let approximate = if distinct_error.is_some() { "~" } else { "" };
+      cells.push(("Unique", format!("{approximate}{total_unique}")));
+      if let Some((value, count)) = frequency_table.first() {
+        let approximate = if frequency_error.is_some() { "~" } else { "" };
+        cells.push(("Top", format!("'{value}' ({approximate}{count})")));
+      }
+      if let Some(strings) = strings {
+        cells.extend([
+          ("Mean Length", number(strings.mean_length)),
+          ("Empty", strings.empty_count.to_string()),
+        ]);
+      }
+    }
+    ColumnStats::Boolean {
+      true_count,
+      false_count,
+      true_percentage,
+      false_percentage,
+      ..
+    } => {
+      let share = |count: &u64, percentage: &Option<f64>| match percentage {
+        Some(percentage) => format!("{count} ({percentage:.1}%)"),
+        None => count.to_string(),
+      };
+      cells.extend([
+        ("True", share(true_count, true_percentage)),
+        ("False", share(false_count, false_percentage)),
+      ]);
+    }
+    ColumnStats::Temporal {
+      earliest,
+      latest,
+      span,
+      ..
+    } => cells.extend([
+      ("Earliest", text(earliest)),
+      ("Latest", text(latest)),
+      ("Span", text(span)),
+    ]),
+    ColumnStats::List {
+      min_length,
+      max_length,
+      mean_length,
+      empty_count,
+      ..
+    } => {
+      let length =
+        |length: &Option<u64>| length.map_or_else(|| "N/A".to_string(), |l| l.to_string());
+      cells.extend([
+        ("Min Length", length(min_length)),
+        ("Max Length", length(max_length)),
+        ("Mean Length", number(*mean_length)),
+        ("Empty", empty_count.to_string()),
+      ]);
+    }
+    ColumnStats::Footer { min, max, .. } => {
+      cells.extend([("Min", text(min)), ("Max", text(max))]);
+    }
+  }
+
+  cells
+}
+
+/// Renders one table per column with a row per statistic and a column per
+/// group, led by the whole dataset.
+pub fn format_groups(grouped: &GroupedSummary, n_rows: usize, columns: &[ColumnSummary]) -> String {
+  let mut output = String::new();
+
+  output.push_str(&format!(
+    "📋 Column Analysis by '{}' ({} of {} groups)\n",
+    grouped.key,
+    grouped.groups.len(),
+    grouped.total_groups
+  ));
+  output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n\n");
+
+  let mut headers = vec![String::new(), "(all)".to_string()];
+  headers.extend(grouped.groups.iter().map(|group| {
+    group
+      .value
+      .clone()
+      .unwrap_or_else(|| NULL_GROUP.to_string())
+  }));
+
+  for (i, column) in columns.iter().enumerate() {
+    output.push_str(&format!(
+      "{}. Column: '{}' ({})\n",
+      i + 1,
+      column.name,
+      column.data_type
+    ));
+
+    // One cell list per table column: the whole dataset, then every group
+    let mut cells = vec![stat_cells(column)];
+    cells.extend(grouped.groups.iter().map(|group| {
+      group
+        .columns
+        .iter()
+        .find(|group_column| group_column.name == column.name)
+        .map(stat_cells)
+        .unwrap_or_default()
+    }));
+
+    // Statistics in order of first appearance, so a group whose column fell
+    // into a different kind still lines up with the others
+    let mut labels: Vec<&str> = vec![];
+    for (label, _) in cells.iter().flatten() {
+      if !labels.contains(label) {
+        labels.push(label);
+      }
+    }
+
+    let mut rows = vec![headers.clone()];
+    let mut row_counts = vec!["Rows".to_string(), n_rows.to_string()];
+    row_counts.extend(grouped.groups.iter().map(|group| group.n_rows.to_string()));
+    rows.push(row_counts);
+    for label in labels {
+      let mut row = vec![label.to_string()];
+      row.extend(cells.iter().map(|column_cells| {
+        column_cells
+          .iter()
+          .find(|(cell_label, _)| *cell_label == label)
+          .map_or_else(|| "-".to_string(), |(_, value)| value.clone())
+      }));
+      rows.push(row);
+    }
+
+    output.push_str(&format_table(&rows));
+    output.push('\n');
+  }
+
+  output
+}
+
+fn format_table(rows: &[Vec<String>]) -> String {
+  let n_columns = rows.iter().map(Vec::len).max().unwrap_or(0);
+  let widths = (0..n_columns)
+    .map(|i| {
+      rows
+        .iter()
+        .filter_map(|row| row.get(i))
+        .map(|cell| cell.chars().count())
+        .max()
+        .unwrap_or(0)
+    })
+    .collect::<Vec<_>>();
+
+  let mut output = String::new();
+  for row in rows {
+    let line = row
+      .iter()
+      .zip(&widths)
+      .enumerate()
+      .map(|(i, (cell, width))| {
+        if i == 0 {
+          format!("{cell:<width$}")
+        } else {
+          format!("{cell:>width$}")
+        }
+      })
+      .collect::<Vec<_>>()
+      .join(" │ ");
+    output.push_str(&format!("   {}\n", line.trim_end()));
+  }
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::expect_numerical;
+
+  fn frame() -> DataFrame {
+    df!(
+      "region" => [Some("EU"), Some("US"), Some("EU"), None, Some("EU"), Some("APAC")],
+      "amount" => [1.0, 10.0, 3.0, 7.0, 5.0, 2.0],
+    )
+    .unwrap()
+  }
+
+  fn mean(column: &ColumnSummary) -> Option<f64> {
+    expect_numerical!(column, { mean, .. });
+    *mean
+  }
+
+  #[test]
+  fn summarizes_the_largest_groups_first() {
+    let summary = Profiler::new()
+      .group_by("region", 3)
+      .profile_frame(&frame())
+      .unwrap();
+    let grouped = summary.group_by.unwrap();
+
+    assert_eq!(grouped.key, "region");
+    assert_eq!(grouped.total_groups, 4);
+    // Ties are broken by value, so APAC comes before the null group
+    let groups = grouped
+      .groups
+      .iter()
+      .map(|group| (group.value.as_deref(), group.n_rows))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      groups,
+      [(Some("EU"), 3), (Some("APAC"), 1), (Some("US"), 1)]
+    );
+
+    // The key splits the groups and is not profiled itself
+    let eu = &grouped.groups[0];
+    assert_eq!(eu.columns.len(), 1);
+    assert_eq!(eu.columns[0].name, "amount");
+    assert_eq!(mean(&eu.columns[0]), Some(3.0));
+  }
+
+  #[test]
+  fn keeps_a_group_for_null_keys() {
+    let summary = Profiler::new()
+      .group_by("region", 10)
+      .profile_frame(&frame())
+      .unwrap();
+    let grouped = summary.group_by.unwrap();
+
+    let null = grouped
+      .groups
+      .iter()
+      .find(|group| group.value.is_none())
+      .unwrap();
+    assert_eq!(null.n_rows, 1);
+    assert_eq!(mean(&null.columns[0]), Some(7.0));
+
+    // A key that is literally "null" is a group of its own
+    let frame = df!(
+      "region" => [Some("null"), None, Some("null")],
+      "amount" => [1.0, 2.0, 3.0],
+    )
+    .unwrap();
+    let summary = Profiler::new()
+      .group_by("region", 10)
+      .profile_frame(&frame)
+      .unwrap();
+    let grouped = summary.group_by.as_ref().unwrap();
+    let groups = grouped
+      .groups
+      .iter()
+      .map(|group| {
+        (
+          group.value.as_deref(),
+          group.n_rows,
+          mean(&group.columns[0]),
+        )
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(groups, [(Some("null"), 2, Some(2.0)), (None, 1, Some(2.0))]);
+    let output = format_groups(grouped, summary.n_rows, &summary.columns);
+    assert!(output.contains("│     null │        ∅\n"), "{output}");
+  }
+
+  #[test]
+  fn lays_groups_out_side_by_side() {
+    let summary = Profiler::new()
+      .group_by("region", 2)
+      .profile_frame(&frame())
+      .unwrap();
+    let output = format_groups(
+      summary.group_by.as_ref().unwrap(),
+      summary.n_rows,
+      &summary.columns,
+    );
+
+    assert!(output.starts_with("📋 Column Analysis by 'region' (2 of 4 groups)\n"));
+    assert!(
+      output.contains("   Rows     │        6 │        3 │        1\n"),
+      "{output}"
+    );
+    assert!(
+      output.contains("   Mean     │   4.6667 │   3.0000 │   2.0000\n"),
+      "{output}"
+    );
+  }
+}
@@ -0,0 +1,343 @@
+//! Histograms of numerical columns, rendered as Unicode bar charts.
+
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{Profiler, VALUE_COLUMN, collect_streaming, kll};
+
+/// Upper bound on the bin count chosen by the Freedman–Diaconis rule, which
+/// can otherwise explode on long-tailed data with a narrow IQR.
+const MAX_AUTO_BINS: usize = 100;
+
+/// Width of the longest bar in the text report, in characters.
+const BAR_WIDTH: usize = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum BinStrategy {
+  /// Bins of equal width between the minimum and maximum
+  EqualWidth,
+  /// Bins holding roughly equal numbers of values
+  Quantile,
+  /// Bin width of 2·IQR/∛n, ignoring the requested bin count
+  FreedmanDiaconis,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Histogram {
+  pub strategy: BinStrategy,
+  pub bins: Vec<HistogramBin>,
+}
+
+/// Values in `[lower, upper)`; the last bin also includes its upper edge.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HistogramBin {
+  pub lower: f64,
+  pub upper: f64,
+  pub count: u64,
+}
+
+/// Bins the finite values of a column. NaN and infinite values are left out,
+/// since they are reported separately and have no place on the axis.
+pub(crate) fn compute_histogram(
+  lazy_frame: &LazyFrame,
+  name: &str,
+  strategy: BinStrategy,
+  n_bins: usize,
+  profiler: &Profiler,
+) -> Result<Option<Histogram>> {
+  let values = lazy_frame
+    .clone()
+    .select([col(name).cast(DataType::Float64).alias(VALUE_COLUMN)])
+    .filter(col(VALUE_COLUMN).is_finite());
+
+  let Some(edges) = bin_edges(&values, strategy, n_bins, profiler)? else {
+    return Ok(None);
+  };
+
+  let value = col(VALUE_COLUMN);
+  let last = edges.len() - 2;
+  let counts = edges
+    .windows(2)
+    .enumerate()
+    .map(|(i, edge)| {
+      let upper = if i == last {
+        value.clone().lt_eq(lit(edge[1]))
+      } else {
+        value.clone().lt(lit(edge[1]))
+      };
+      value
+        .clone()
+        .gt_eq(lit(edge[0]))
+        .and(upper)
+        .sum()
+        .alias(format!("{i}"))
+    })
+    .collect::<Vec<_>>();
+
+  let counts = collect_streaming(values.select(counts))
+    .with_context(|| "Failed to compute histogram bin counts")?;
+
+  let bins = edges
+    .windows(2)
+    .enumerate()
+    .map(|(i, edge)| {
+      let count = counts
+        .column(&format!("{i}"))?
+        .get(0)?
+        .extract::<u64>()
+        .unwrap_or(0);
+      Ok(HistogramBin {
+        lower: edge[0],
+        upper: edge[1],
+        count,
+      })
+    })
+    .collect::<Result<Vec<_>>>()?;
+
+  Ok(Some(Histogram { strategy, bins }))
+}
+
+/// Edges of the bins in ascending order, or `None` without finite values.
+fn bin_edges(
+  values: &LazyFrame,
+  strategy: BinStrategy,
+  n_bins: usize,
+  profiler: &Profiler,
+) -> Result<Option<Vec<f64>>> {
+  let quantiles: Vec<(String, f64)> = match strategy {
+    BinStrategy::EqualWidth => vec![],
+    BinStrategy::Quantile => (1..n_bins)
+      .map(|i| (format!("q{i}"), i as f64 / n_bins as f64))
+      .collect(),
+    BinStrategy::FreedmanDiaconis => vec![("q25".to_string(), 0.25), ("q75".to_string(), 0.75)],
+  };
+
+  let value = col(VALUE_COLUMN);
+  let mut exprs = vec![
+    value.clone().min().alias("min"),
+    value.clone().max().alias("max"),
+    value.clone().count().alias("count"),
+  ];
+  // With a quantile sketch, the edges come from a separate streaming pass
+  // rather than from sorting the column
+  let sketch = match profiler.quantile_sketch {
+    Some(k) if !quantiles.is_empty() => Some(kll::sketch_column(values, VALUE_COLUMN, k)?),
+    _ => {
+      exprs.extend(quantiles.iter().map(|(name, quantile)| {
+        value
+          .clone()
+          .quantile(lit(*quantile), profiler.quantile_method)
+          .alias(name.as_str())
+      }));
+      None
+    }
+  };
+
+  let stats = collect_streaming(values.clone().select(exprs))
+    .with_context(|| "Failed to compute histogram bin edges")?;
+  let stat = |name: &str| -> Option<f64> {
+    if let Some(sketch) = &sketch
+      && let Some((_, quantile)) = quantiles.iter().find(|(stat, _)| stat == name)
+    {
+      return sketch.quantile(*quantile);
+    }
+    let value = stats.column(name).ok()?.get(0).ok()?;
+    value.extract::<f64>()
+  };
+
+  let (Some(min), Some(max)) = (stat("min"), stat("max")) else {
+    return Ok(None);
+  };
+  if min == max {
+    return Ok(Some(vec![min, max]));
+  }
+
+  let edges = match strategy {
+    BinStrategy::EqualWidth => equal_width_edges(min, max, n_bins),
+    BinStrategy::Quantile => {
+      let mut edges = vec![min];
+      edges.extend((1..n_bins).filter_map(|i| stat(&format!("q{i}"))));
+      edges.push(max);
+      // Heavily repeated values produce duplicate quantiles
+      edges.dedup();
+      edges
+    }
+    BinStrategy::FreedmanDiaconis => {
+      let count = stat("count").unwrap_or(0.0);
+      let iqr = match (stat("q25"), stat("q75")) {
+        (Some(q25), Some(q75)) => q75 - q25,
+        _ => 0.0,
+      };
+      let width = 2.0 * iqr / count.cbrt();
+      let n_bins = if width > 0.0 {
+        (((max - min) / width).ceil() as usize).clamp(1, MAX_AUTO_BINS)
+      } else {
+        n_bins
+      };
+      equal_width_edges(min, max, n_bins)
+    }
+  };
+
+  Ok(Some(edges))
+}
+
+fn equal_width_edges(min: f64, max: f64, n_bins: usize) -> Vec<f64> {
+  let width = (max - min) / n_bins as f64;
+  let mut edges = (0..n_bins)
+    .map(|i| min + width * i as f64)
+    .collect::<Vec<_>>();
+  // Pin the last edge so rounding cannot leave the maximum outside
+  edges.push(max);
+  edges
+}
+
+/// Renders one line per bin with a bar scaled to the fullest bin.
+pub fn format_histogram(histogram: &Histogram) -> String {
+  const EIGHTHS: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+
+  let peak = histogram
+    .bins
+    .iter()
+    .map(|bin| bin.count)
+    .max()
+    .unwrap_or(0);
+  let last = histogram.bins.len().saturating_sub(1);
+  let labels = histogram
+    .bins
+    .iter()
+    .enumerate()
+    .map(|(i, bin)| {
+      let close = if i == last { ']' } else { ')' };
+      format!("[{:.4}, {:.4}{close}", bin.lower, bin.upper)
+    })
+    .collect::<Vec<_>>();
+  let label_width = labels
+    .iter()
+    .map(|label| label.chars().count())
+    .max()
+    .unwrap_or(0);
+
+  let mut output = String::new();
+  for (bin, label) in histogram.bins.iter().zip(&labels) {
+    let eighths = if peak > 0 {
+      (bin.count as f64 / peak as f64 * (BAR_WIDTH * 8) as f64).round() as usize
+    } else {
+      0
+    };
+    let bar = format!("{}{}", "█".repeat(eighths / 8), EIGHTHS[eighths % 8]);
+    output.push_str(&format!(
+      "        {label:<label_width$} {bar:<BAR_WIDTH$} {}\n",
+      bin.count
+    ));
+  }
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn bin(values: Vec<f64>, strategy: BinStrategy, n_bins: usize) -> Option<Histogram> {
+    bin_with(values, strategy, n_bins, &Profiler::new())
+  }
+
+  fn bin_with(
+    values: Vec<f64>,
+    strategy: BinStrategy,
+    n_bins: usize,
+    profiler: &Profiler,
+  ) -> Option<Histogram> {
+    let frame = df!("value" => values).unwrap().lazy();
+    compute_histogram(&frame, "value", strategy, n_bins, profiler).unwrap()
+  }
+
+  fn counts(histogram: &Histogram) -> Vec<u64> {
+    histogram.bins.iter().map(|bin| bin.count).collect()
+  }
+
+  #[test]
+  fn bins_equal_widths_with_the_maximum_in_the_last_bin() {
+    let values = (0..10).map(f64::from).collect();
+    let histogram = bin(values, BinStrategy::EqualWidth, 5).unwrap();
+
+    assert_eq!(counts(&histogram), [2, 2, 2, 2, 2]);
+    let edges = histogram
+      .bins
+      .iter()
+      .map(|bin| bin.lower)
+      .collect::<Vec<_>>();
+    assert_eq!(edges, [0.0, 1.8, 3.6, 5.4, 7.2]);
+    assert_eq!(histogram.bins[4].upper, 9.0);
+  }
+
+  #[test]
+  fn leaves_out_nan_and_infinities() {
+    let values = vec![1.0, f64::NAN, 2.0, f64::INFINITY, f64::NEG_INFINITY, 3.0];
+    let histogram = bin(values, BinStrategy::EqualWidth, 2).unwrap();
+
+    assert_eq!(counts(&histogram), [1, 2]);
+    assert_eq!(
+      (histogram.bins[0].lower, histogram.bins[1].upper),
+      (1.0, 3.0)
+    );
+    assert!(bin(vec![f64::NAN], BinStrategy::EqualWidth, 2).is_none());
+  }
+
+  #[test]
+  fn puts_a_constant_column_in_one_bin() {
+    let histogram = bin(vec![4.0; 5], BinStrategy::Quantile, 4).unwrap();
+    assert_eq!(counts(&histogram), [5]);
+  }
+
+  #[test]
+  fn merges_repeated_quantile_edges() {
+    // Most values are zero, so the lower quantiles coincide
+    let mut values = vec![0.0; 90];
+    values.extend((1..=10).map(f64::from));
+    for profiler in [Profiler::new(), Profiler::new().approx_quantiles(200)] {
+      let histogram = bin_with(values.clone(), BinStrategy::Quantile, 4, &profiler).unwrap();
+      assert_eq!(histogram.bins.len(), 1, "{histogram:?}");
+      assert_eq!(counts(&histogram), [100]);
+    }
+  }
+
+  #[test]
+  fn sizes_freedman_diaconis_bins_from_the_iqr() {
+    // IQR 50 of 101 values gives bins 100 / 21.54 wide, so 5 of them
+    let values = (0..=100).map(f64::from).collect();
+    let histogram = bin(values, BinStrategy::FreedmanDiaconis, 99).unwrap();
+    assert_eq!(histogram.bins.len(), 5);
+    assert_eq!(counts(&histogram).iter().sum::<u64>(), 101);
+  }
+
+  #[test]
+  fn scales_bars_to_the_fullest_bin() {
+    let histogram = Histogram {
+      strategy: BinStrategy::EqualWidth,
+      bins: vec![
+        HistogramBin {
+          lower: 0.0,
+          upper: 1.0,
+          count: 4,
+        },
+        HistogramBin {
+          lower: 1.0,
+          upper: 2.0,
+          count: 1,
+        },
+      ],
+    };
+    let lines = format_histogram(&histogram)
+      .lines()
+      .map(str::to_string)
+      .collect::<Vec<_>>();
+
+    assert!(lines[0].contains(&format!("[0.0000, 1.0000) {} 4", "█".repeat(BAR_WIDTH))));
+    // A quarter of 30 characters is 7 and a half
+    assert!(lines[1].contains(&format!("[1.0000, 2.0000] {}▌", "█".repeat(7))));
+    assert!(lines[1].ends_with(" 1"));
+  }
+}
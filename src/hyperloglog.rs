@@ -0,0 +1,228 @@
+//! Approximate distinct counts with HyperLogLog.
+//!
+//! Every non-null value is hashed to 64 bits: the low bits pick one of `2^p`
+//! registers and the leading zeros of the rest give its rank. Registers keep
+//! the largest rank they have seen, so sketches of separate row groups or
+//! files merge by taking the register-wise maximum. The registers are built
+//! by a streaming group-by over the register index, which performs exactly
+//! that merge and needs memory for `2^p` registers rather than every
+//! distinct value.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collect_streaming;
+
+const REGISTER_COLUMN: &str = "__register";
+const RANK_COLUMN: &str = "__rank";
+
+/// Fixed hash seeds, so sketches of separate inputs can be merged.
+const SEEDS: (u64, u64, u64, u64) = (
+  0x243f_6a88_85a3_08d3,
+  0x1319_8a2e_0370_7344,
+  0xa409_3822_299f_31d0,
+  0x082e_fa98_ec4e_6c89,
+);
+
+pub const MIN_PRECISION: u8 = 4;
+pub const MAX_PRECISION: u8 = 18;
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HyperLogLog {
+  precision: u8,
+  registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+  /// An empty sketch with `2^precision` registers.
+  pub fn new(precision: u8) -> Self {
+    let precision = precision.clamp(MIN_PRECISION, MAX_PRECISION);
+    Self {
+      precision,
+      registers: vec![0; 1 << precision],
+    }
+  }
+
+  /// The smallest sketch whose relative standard error is at most
+  /// `relative_error` (e.g. `0.01` for 1%), capped at the largest precision.
+  pub fn with_error(relative_error: f64) -> Self {
+    let registers = (1.04 / relative_error).powi(2);
+    let precision = registers.log2().ceil().clamp(0.0, f64::from(MAX_PRECISION));
+    Self::new(precision as u8)
+  }
+
+  pub fn precision(&self) -> u8 {
+    self.precision
+  }
+
+  /// Relative standard error of the estimate, `1.04 / sqrt(2^p)`.
+  pub fn relative_error(&self) -> f64 {
+    1.04 / (self.registers.len() as f64).sqrt()
+  }
+
+  fn observe(&mut self, register: usize, rank: u8) {
+    let current = &mut self.registers[register];
+    *current = (*current).max(rank);
+  }
+
+  /// Folds another sketch into this one. Both must have been built with the
+  /// same precision.
+  pub fn merge(&mut self, other: &HyperLogLog) -> Result<()> {
+    if other.precision != self.precision {
+      anyhow::bail!(
+        "Cannot merge HyperLogLog sketches of precision {} and {}",
+        self.precision,
+        other.precision
+      );
+    }
+    for (register, rank) in other.registers.iter().enumerate() {
+      self.observe(register, *rank);
+    }
+    Ok(())
+  }
+
+  /// Estimated number of distinct values, using linear counting while many
+  /// registers are still empty.
+  pub fn estimate(&self) -> f64 {
+    let m = self.registers.len() as f64;
+    let alpha = match self.registers.len() {
+      16 => 0.673,
+      32 => 0.697,
+      64 => 0.709,
+      _ => 0.7213 / (1.0 + 1.079 / m),
+    };
+    let harmonic_sum = self
+      .registers
+      .iter()
+      .map(|rank| 2f64.powi(-i32::from(*rank)))
+      .sum::<f64>();
+    let raw = alpha * m * m / harmonic_sum;
+
+    let empty = self.registers.iter().filter(|rank| **rank == 0).count();
+    if raw <= 2.5 * m && empty > 0 {
+      m * (m / empty as f64).ln()
+    } else {
+      raw
+    }
+  }
+}
+
+/// Sketches the non-null values of a column with a streaming group-by, one
+/// row per register.
+pub(crate) fn sketch_column(
+  lazy_frame: &LazyFrame,
+  name: &str,
+  precision: u8,
+) -> Result<HyperLogLog> {
+  let mut sketch = HyperLogLog::new(precision);
+  let width = 64 - u32::from(sketch.precision);
+
+  let (k0, k1, k2, k3) = SEEDS;
+  let hash = col(name).hash(k0, k1, k2, k3);
+  let registers = lazy_frame
+    .clone()
+    .select([col(name)])
+    .filter(col(name).is_not_null())
+    .select([
+      (hash.clone() % lit(1u64 << sketch.precision)).alias(REGISTER_COLUMN),
+      hash
+        .bitwise_leading_zeros()
+        .cast(DataType::UInt32)
+        .alias(RANK_COLUMN),
+    ])
+    .group_by([col(REGISTER_COLUMN)])
+    .agg([col(RANK_COLUMN).max()]);
+
+  let registers =
+    collect_streaming(registers).with_context(|| "Failed to build HyperLogLog sketch")?;
+  let indices = registers.column(REGISTER_COLUMN)?.cast(&DataType::UInt64)?;
+  let ranks = registers.column(RANK_COLUMN)?.cast(&DataType::UInt32)?;
+  // The rank is the position of the first set bit above the register bits,
+  // so each extra leading zero halves the odds of reaching it
+  for (register, leading_zeros) in indices.u64()?.into_iter().zip(ranks.u32()?) {
+    if let (Some(register), Some(leading_zeros)) = (register, leading_zeros) {
+      sketch.observe(register as usize, (leading_zeros.min(width) + 1) as u8);
+    }
+  }
+
+  Ok(sketch)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn sketch(values: impl IntoIterator<Item = Option<u64>>, precision: u8) -> HyperLogLog {
+    let values = values.into_iter().collect::<Vec<_>>();
+    let frame = df!("value" => values).unwrap();
+    sketch_column(&frame.lazy(), "value", precision).unwrap()
+  }
+
+  /// Asserts the estimate is within four standard errors of `truth`.
+  fn assert_close(sketch: &HyperLogLog, truth: f64) {
+    let estimate = sketch.estimate();
+    let tolerance = 4.0 * sketch.relative_error() * truth;
+    assert!(
+      (estimate - truth).abs() <= tolerance,
+      "estimate {estimate} is not within {tolerance} of {truth}"
+    );
+  }
+
+  #[test]
+  fn estimates_small_counts_by_linear_counting() {
+    for distinct in [1, 10, 100, 1000] {
+      // Every value appears three times
+      let values = (0..3 * distinct).map(|i| Some(i % distinct));
+      assert_close(&sketch(values, 12), distinct as f64);
+    }
+  }
+
+  #[test]
+  fn estimates_large_counts() {
+    for distinct in [50_000, 300_000] {
+      let values = (0..2 * distinct).map(|i| Some(i % distinct));
+      assert_close(&sketch(values, 12), distinct as f64);
+    }
+  }
+
+  #[test]
+  fn empty_sketch_estimates_zero() {
+    assert_eq!(HyperLogLog::new(10).estimate(), 0.0);
+    assert_eq!(sketch([None, None], 10).estimate(), 0.0);
+  }
+
+  #[test]
+  fn merging_equals_sketching_the_union() {
+    let mut merged = sketch((0..50_000).map(Some), 11);
+    merged
+      .merge(&sketch((25_000..75_000).map(Some), 11))
+      .unwrap();
+
+    assert_eq!(merged, sketch((0..75_000).map(Some), 11));
+    assert_close(&merged, 75_000.0);
+  }
+
+  #[test]
+  fn refuses_to_merge_different_precisions() {
+    let mut sketch = HyperLogLog::new(10);
+    assert!(sketch.merge(&HyperLogLog::new(11)).is_err());
+  }
+
+  #[test]
+  fn ignores_nulls() {
+    let with_nulls = (0..10_000).map(|i| (i % 3 != 0).then_some(i));
+    let without_nulls = (0..10_000).filter(|i| i % 3 != 0).map(Some);
+
+    assert_eq!(sketch(with_nulls, 12), sketch(without_nulls, 12));
+  }
+
+  #[test]
+  fn chooses_the_smallest_precision_for_the_error() {
+    let sketch = HyperLogLog::with_error(0.01);
+    assert_eq!(sketch.precision(), 14);
+    assert!(sketch.relative_error() <= 0.01);
+    assert_eq!(HyperLogLog::with_error(1e-9).precision(), MAX_PRECISION);
+    assert_eq!(HyperLogLog::with_error(0.9).precision(), MIN_PRECISION);
+  }
+}
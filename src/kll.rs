@@ -0,0 +1,361 @@
+//! Approximate quantiles with a KLL sketch.
+//!
+//! The sketch is a stack of compactors: level `h` holds values that each
+//! stand for `2^h` inputs. When a level fills up it is sorted and every other
+//! value (starting at a random offset) is promoted to the level above, so
+//! memory grows with `k · log(n / k)` rather than with the number of rows.
+//! Sketches of separate batches, row groups, or files merge by concatenating
+//! their levels and compacting again, with the same error guarantee.
+//!
+//! Values reach the sketch through an elementwise map on the streaming
+//! engine: every morsel is sketched on its own and merged into a shared
+//! sketch, so the column is never materialized or sorted as a whole.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::sync::{Arc, Mutex};
+
+use crate::{collect_streaming, sketch_batches};
+
+/// Capacity of each level relative to the one above it.
+const LEVEL_DECAY: f64 = 2.0 / 3.0;
+
+/// Smallest capacity of any level.
+const MIN_CAPACITY: usize = 2;
+
+pub const MIN_K: usize = 8;
+
+#[derive(Clone, Debug)]
+pub struct KllSketch {
+  k: usize,
+  /// `levels[h]` holds values of weight `2^h`
+  levels: Vec<Vec<f64>>,
+  /// Number of values held across all levels
+  size: usize,
+  /// Size that triggers a compaction, the sum of the level capacities
+  max_size: usize,
+  count: u64,
+  /// State of the xorshift generator choosing compaction offsets, fixed so
+  /// sketches are reproducible
+  coin: u64,
+}
+
+impl KllSketch {
+  /// An empty sketch whose top level holds `k` values; larger `k` means
+  /// smaller rank error and more memory.
+  pub fn new(k: usize) -> Self {
+    let mut sketch = Self {
+      k: k.max(MIN_K),
+      levels: vec![],
+      size: 0,
+      max_size: 0,
+      count: 0,
+      coin: 0x9e37_79b9_7f4a_7c15,
+    };
+    sketch.grow();
+    sketch
+  }
+
+  /// Normalized rank error of quantile estimates at 99% confidence, e.g.
+  /// `0.0133` for `k = 200`: the value reported for quantile `q` has a rank
+  /// within `q ± error` of all values.
+  pub fn rank_error(&self) -> f64 {
+    2.296 / (self.k as f64).powf(0.9723)
+  }
+
+  /// Number of values added to the sketch.
+  pub fn count(&self) -> u64 {
+    self.count
+  }
+
+  pub fn insert(&mut self, value: f64) {
+    self.levels[0].push(value);
+    self.size += 1;
+    self.count += 1;
+    if self.size >= self.max_size {
+      self.compress();
+    }
+  }
+
+  /// Folds another sketch into this one.
+  pub fn merge(&mut self, other: &KllSketch) {
+    while self.levels.len() < other.levels.len() {
+      self.grow();
+    }
+    for (level, values) in other.levels.iter().enumerate() {
+      self.levels[level].extend_from_slice(values);
+    }
+    self.size = self.levels.iter().map(Vec::len).sum();
+    self.count += other.count;
+    while self.size >= self.max_size {
+      self.compress();
+    }
+  }
+
+  /// The value whose rank is closest to `quantile` (0-1) of the values, or
+  /// `None` for an empty sketch.
+  pub fn quantile(&self, quantile: f64) -> Option<f64> {
+    let mut weighted = self
+      .levels
+      .iter()
+      .enumerate()
+      .flat_map(|(level, values)| values.iter().map(move |value| (*value, 1u64 << level)))
+      .collect::<Vec<_>>();
+    weighted.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let total = weighted.iter().map(|(_, weight)| weight).sum::<u64>();
+    let target = quantile.clamp(0.0, 1.0) * total as f64;
+    let mut rank = 0;
+    for (value, weight) in &weighted {
+      rank += weight;
+      if rank as f64 >= target {
+        return Some(*value);
+      }
+    }
+    weighted.last().map(|(value, _)| *value)
+  }
+
+  fn capacity(&self, level: usize) -> usize {
+    let depth = self.levels.len() - level - 1;
+    let capacity = (self.k as f64 * LEVEL_DECAY.powi(depth as i32)).ceil() as usize;
+    capacity.max(MIN_CAPACITY)
+  }
+
+  fn grow(&mut self) {
+    self.levels.push(vec![]);
+    self.max_size = (0..self.levels.len())
+      .map(|level| self.capacity(level))
+      .sum();
+  }
+
+  /// Compacts the lowest level that is over capacity into the one above.
+  fn compress(&mut self) {
+    for level in 0..self.levels.len() {
+      if self.levels[level].len() < self.capacity(level) {
+        continue;
+      }
+      if level + 1 == self.levels.len() {
+        self.grow();
+      }
+
+      let mut values = std::mem::take(&mut self.levels[level]);
+      values.sort_by(f64::total_cmp);
+      // An odd value out stays behind, so every promoted value stands for
+      // exactly two
+      if values.len() % 2 == 1 {
+        self.levels[level].push(values.pop().unwrap_or_default());
+      }
+      let offset = usize::from(self.flip());
+      let promoted = values.iter().skip(offset).step_by(2).copied();
+      self.levels[level + 1].extend(promoted);
+
+      self.size = self.levels.iter().map(Vec::len).sum();
+      break;
+    }
+  }
+
+  fn flip(&mut self) -> bool {
+    self.coin ^= self.coin << 13;
+    self.coin ^= self.coin >> 7;
+    self.coin ^= self.coin << 17;
+    self.coin & 1 == 1
+  }
+}
+
+/// Sketches the finite values of a numerical column in one streaming pass;
+/// nulls, NaN, and infinities are left out.
+pub(crate) fn sketch_column(lazy_frame: &LazyFrame, name: &str, k: usize) -> Result<KllSketch> {
+  let mut sketches = sketch_columns(lazy_frame, &[name], k)?;
+  Ok(sketches.remove(0))
+}
+
+/// Sketches several numerical columns in the same streaming pass, one sketch
+/// per column in the order of `names`.
+pub(crate) fn sketch_columns(
+  lazy_frame: &LazyFrame,
+  names: &[&str],
+  k: usize,
+) -> Result<Vec<KllSketch>> {
+  let sketches = names
+    .iter()
+    .map(|_| Arc::new(Mutex::new(KllSketch::new(k))))
+    .collect::<Vec<_>>();
+
+  let exprs = sketches
+    .iter()
+    .zip(names)
+    .enumerate()
+    .map(|(i, (sketch, name))| {
+      let batch = move |column: &Column| {
+        let mut batch = KllSketch::new(k);
+        for value in column.f64()?.into_iter().flatten() {
+          if value.is_finite() {
+            batch.insert(value);
+          }
+        }
+        Ok(batch)
+      };
+      sketch_batches(
+        col(*name).cast(DataType::Float64),
+        sketch,
+        batch,
+        KllSketch::merge,
+      )
+      .alias(format!("{i}"))
+    })
+    .collect::<Vec<_>>();
+  collect_streaming(lazy_frame.clone().select(exprs))
+    .with_context(|| format!("Failed to sketch quantiles of '{}'", names.join("', '")))?;
+
+  sketches
+    .iter()
+    .map(|sketch| {
+      let sketch = sketch
+        .lock()
+        .map_err(|_| anyhow::anyhow!("Quantile sketch lock poisoned"))?;
+      Ok(sketch.clone())
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// SplitMix64 values in `[0, 1)`, so the streams are reproducible.
+  fn uniform(n: usize, seed: u64) -> Vec<f64> {
+    let mut state = seed;
+    (0..n)
+      .map(|_| {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        (z ^ (z >> 31)) as f64 / 2f64.powi(64)
+      })
+      .collect()
+  }
+
+  /// Heavy-tailed values, mostly near zero with a few huge ones.
+  fn skewed(n: usize, seed: u64) -> Vec<f64> {
+    uniform(n, seed)
+      .into_iter()
+      .map(|u| (1.0 - u).powf(-2.0).floor())
+      .collect()
+  }
+
+  fn sketch_of(values: &[f64], k: usize) -> KllSketch {
+    let mut sketch = KllSketch::new(k);
+    for value in values {
+      sketch.insert(*value);
+    }
+    sketch
+  }
+
+  /// Asserts every reported quantile has an exact rank within the sketch's
+  /// rank error of the requested one.
+  fn assert_rank_error(sketch: &KllSketch, values: &[f64]) {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let n = sorted.len() as f64;
+    let error = sketch.rank_error();
+
+    for i in 0..=100 {
+      let quantile = f64::from(i) / 100.0;
+      let value = sketch.quantile(quantile).unwrap();
+      // With ties the value covers a range of ranks
+      let below = sorted.partition_point(|x| *x < value) as f64 / n;
+      let at_most = sorted.partition_point(|x| *x <= value) as f64 / n;
+      assert!(
+        below - error <= quantile && quantile <= at_most + error,
+        "q{quantile}: {value} has ranks {below}-{at_most}, beyond ±{error}"
+      );
+    }
+  }
+
+  #[test]
+  fn reports_the_rank_error_of_its_size() {
+    assert!((KllSketch::new(200).rank_error() - 0.0133).abs() < 0.0001);
+    assert!(KllSketch::new(400).rank_error() < KllSketch::new(200).rank_error());
+    assert_eq!(
+      KllSketch::new(1).rank_error(),
+      KllSketch::new(MIN_K).rank_error()
+    );
+  }
+
+  #[test]
+  fn quantiles_of_a_uniform_stream_are_within_the_rank_error() {
+    let values = uniform(200_000, 1);
+    let sketch = sketch_of(&values, 200);
+
+    assert_eq!(sketch.count(), 200_000);
+    assert_rank_error(&sketch, &values);
+  }
+
+  #[test]
+  fn quantiles_of_a_skewed_stream_are_within_the_rank_error() {
+    let values = skewed(200_000, 2);
+    assert_rank_error(&sketch_of(&values, 200), &values);
+  }
+
+  #[test]
+  fn quantiles_of_a_sorted_stream_are_within_the_rank_error() {
+    let values = (0..100_000).map(f64::from).collect::<Vec<_>>();
+    assert_rank_error(&sketch_of(&values, 100), &values);
+  }
+
+  #[test]
+  fn small_streams_are_exact() {
+    let sketch = sketch_of(&[3.0, 1.0, 2.0], 200);
+
+    assert_eq!(sketch.quantile(0.0), Some(1.0));
+    assert_eq!(sketch.quantile(0.5), Some(2.0));
+    assert_eq!(sketch.quantile(1.0), Some(3.0));
+    assert_eq!(KllSketch::new(200).quantile(0.5), None);
+  }
+
+  #[test]
+  fn merged_sketches_match_one_combined_sketch() {
+    let values = skewed(200_000, 3);
+    let combined = sketch_of(&values, 200);
+
+    let mut merged = KllSketch::new(200);
+    for chunk in values.chunks(30_000) {
+      merged.merge(&sketch_of(chunk, 200));
+    }
+
+    assert_eq!(merged.count(), combined.count());
+    assert_rank_error(&merged, &values);
+  }
+
+  #[test]
+  fn sketching_a_column_leaves_out_nulls_nan_and_infinities() {
+    let mut values = uniform(10_000, 4).into_iter().map(Some).collect::<Vec<_>>();
+    let finite = values.iter().flatten().copied().collect::<Vec<_>>();
+    values.extend([
+      None,
+      Some(f64::NAN),
+      Some(f64::INFINITY),
+      Some(f64::NEG_INFINITY),
+    ]);
+    let frame = df!("value" => values).unwrap();
+
+    let sketch = sketch_column(&frame.lazy(), "value", 200).unwrap();
+
+    assert_eq!(sketch.count(), finite.len() as u64);
+    assert!(sketch.quantile(0.0).unwrap().is_finite());
+    assert!(sketch.quantile(1.0).unwrap().is_finite());
+    assert_rank_error(&sketch, &finite);
+  }
+
+  #[test]
+  fn sketching_an_integer_column() {
+    let frame = df!("value" => (0..50_000i64).collect::<Vec<_>>()).unwrap();
+
+    let sketch = sketch_column(&frame.lazy(), "value", 200).unwrap();
+
+    assert_eq!(sketch.count(), 50_000);
+    let values = (0..50_000).map(f64::from).collect::<Vec<_>>();
+    assert_rank_error(&sketch, &values);
+  }
+}
@@ -0,0 +1,1922 @@
+//! Profiling of Parquet datasets with Polars: shape, per-column statistics,
+//! frequency tables, temporal coverage, and histograms, computed on the
+//! streaming engine so memory stays bounded.
+//!
+//! ```no_run
+//! use parquet_summarizer::Profiler;
+//!
+//! let summary = Profiler::new()
+//!   .columns(["price", "country"])
+//!   .filter("country = 'KR'")
+//!   .percentiles([5.0, 95.0])
+//!   .profile_path("data.parquet")?;
+//!
+//! for column in &summary.columns {
+//!   println!("{}: {:?}", column.name, column.summary);
+//! }
+//! # Ok::<(), anyhow::Error>(())
+//! ```
+
+pub mod baseline;
+pub mod diff;
+pub mod groups;
+pub mod histogram;
+pub mod hyperloglog;
+pub mod kll;
+pub mod metadata;
+pub mod preview;
+pub mod report;
+pub mod rules;
+pub mod sampling;
+mod selection;
+pub mod space_saving;
+pub mod strings;
+mod temporal;
+#[cfg(test)]
+mod test_support;
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use baseline::BaselineCheck;
+use groups::GroupedSummary;
+use histogram::{BinStrategy, Histogram};
+use hyperloglog::HyperLogLog;
+use metadata::FooterMetadata;
+use preview::{Preview, PreviewRows};
+use rules::RulesReport;
+use sampling::{ConfidenceInterval, Sample, SampleSummary};
+use selection::ColumnSelection;
+use strings::StringStats;
+
+/// Version of the JSON document layout. Bump whenever a field is renamed,
+/// removed, or changes meaning so downstream consumers can detect it.
+pub const SCHEMA_VERSION: u32 = 3;
+
+/// Source label of summaries profiled from a `LazyFrame` or `DataFrame`.
+const IN_MEMORY: &str = "<in-memory>";
+
+#[derive(Debug, Serialize)]
+pub struct ParquetSummary {
+  pub schema_version: u32,
+  pub file: String,
+  /// The `--where` predicate; `n_rows` and all statistics cover matching rows
+  pub filter: Option<String>,
+  /// How rows were sampled; `n_rows` and all statistics then cover the
+  /// sampled rows and are estimates
+  pub sample: Option<SampleSummary>,
+  pub n_rows: usize,
+  pub n_columns: usize,
+  /// Empty when profiling an in-memory frame
+  pub files: Vec<FileSummary>,
+  pub metadata: Option<Vec<FooterMetadata>>,
+  /// Example rows with `--head`, `--tail`, or `--random-rows`
+  pub preview: Option<Preview>,
+  pub columns: Vec<ColumnSummary>,
+  /// Per-group summaries with `--group-by`
+  pub group_by: Option<GroupedSummary>,
+  /// Result of `--check-baseline`
+  pub baseline_check: Option<BaselineCheck>,
+  /// Results of `--rules`
+  pub rules: Option<RulesReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileSummary {
+  pub path: String,
+  pub n_rows: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ColumnSummary {
+  pub name: String,
+  pub data_type: String,
+  pub null_count: Option<u64>,
+  pub null_percentage: Option<f64>,
+  /// Only present when profiling a sample
+  pub null_percentage_interval: Option<ConfidenceInterval>,
+  pub summary: ColumnStats,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ColumnStats {
+  Numerical {
+    /// Only reported for floating point columns
+    nan_count: Option<u64>,
+    infinite_count: Option<u64>,
+    min: Option<f64>,
+    max: Option<f64>,
+    sum: Option<f64>,
+    mean: Option<f64>,
+    /// Only present when profiling a sample
+    mean_interval: Option<ConfidenceInterval>,
+    std_dev: Option<f64>,
+    median: Option<f64>,
+    q25: Option<f64>,
+    q75: Option<f64>,
+    iqr: Option<f64>,
+    percentiles: Vec<Percentile>,
+    /// Only present with `--histogram`
+    histogram: Option<Histogram>,
+    /// Only present for Decimal columns
+    decimal: Option<DecimalStats>,
+    /// Normalized rank error of the median, quartiles, and percentiles, in
+    /// percent, when they are estimated with a KLL sketch
+    quantile_rank_error: Option<f64>,
+  },
+  Categorical {
+    frequency_table: Vec<(String, u64)>,
+    total_unique: usize,
+    /// Relative standard error of `total_unique`, in percent, when it is a
+    /// HyperLogLog estimate
+    distinct_error: Option<f64>,
+    showing_top_n: bool,
+    /// Most that any count in `frequency_table` can overstate the true
+    /// count, when the counts are Space-Saving estimates
+    frequency_error: Option<u64>,
+    /// Only present for String, Categorical, Enum, and Binary columns
+    strings: Option<StringStats>,
+  },
+  /// Percentages are of all rows, so they add up to 100 with the null
+  /// percentage
+  Boolean {
+    true_count: u64,
+    false_count: u64,
+    true_percentage: Option<f64>,
+    false_percentage: Option<f64>,
+    /// Only present when profiling a sample
+    true_percentage_interval: Option<ConfidenceInterval>,
+    false_percentage_interval: Option<ConfidenceInterval>,
+  },
+  Temporal {
+    earliest: Option<String>,
+    latest: Option<String>,
+    span: Option<String>,
+    timezone: Option<String>,
+    /// Most common step between consecutive distinct values (Date and
+    /// Datetime only)
+    cadence: Option<String>,
+    gap_count: Option<u64>,
+    largest_gap: Option<String>,
+    /// Last value the cadence and gaps were measured through, when only
+    /// the earliest values of a large column were
+    gaps_through: Option<String>,
+    by_year: Vec<(String, u32)>,
+    by_month: Vec<(String, u32)>,
+    by_weekday: Vec<(String, u32)>,
+  },
+  /// List and Array columns
+  List {
+    min_length: Option<u64>,
+    max_length: Option<u64>,
+    mean_length: Option<f64>,
+    empty_count: u64,
+    /// Share of the non-null lists that are empty
+    empty_percentage: Option<f64>,
+    element_count: u64,
+    /// Summary of the element values across all lists, named `column[]`
+    /// (one per field for lists of structs)
+    elements: Vec<ColumnSummary>,
+  },
+  /// Built from Parquet column chunk statistics alone (`--metadata-only`)
+  Footer {
+    min: Option<String>,
+    max: Option<String>,
+    distinct_count: Option<u64>,
+    /// List columns, whose chunks record statistics of their elements only
+    nested: bool,
+  },
+}
+
+/// Exact statistics of a Decimal column at its declared scale. The float
+/// statistics next to them may round, which matters when reconciling totals.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DecimalStats {
+  pub precision: Option<usize>,
+  pub scale: usize,
+  pub min: Option<String>,
+  pub max: Option<String>,
+  pub sum: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Percentile {
+  pub percentile: f64,
+  pub value: Option<f64>,
+}
+
+/// Options of a profiling run, set with the builder methods and shared by
+/// every entry point. The defaults match the CLI's.
+#[derive(Clone, Debug)]
+pub struct Profiler {
+  columns: Vec<String>,
+  exclude_columns: Vec<String>,
+  filter: Option<String>,
+  group_by: Option<String>,
+  max_groups: usize,
+  categorical_threshold: usize,
+  percentiles: Vec<f64>,
+  quantile_method: QuantileMethod,
+  /// Size parameter `k` of the KLL sketch, when quantiles are approximated
+  quantile_sketch: Option<usize>,
+  /// Bin strategy and bin count, when histograms are requested
+  histogram: Option<(BinStrategy, usize)>,
+  /// Relative standard error of distinct count estimates, in percent, when
+  /// they are approximated
+  approx_distinct: Option<f64>,
+  /// Number of top values and of Space-Saving counters, when frequency
+  /// tables of high-cardinality columns are approximated
+  heavy_hitters: Option<(usize, usize)>,
+  /// Rows to profile instead of all of them, and the seed drawing them
+  sample: Option<Sample>,
+  seed: u64,
+  /// Which example rows to show, and how many
+  preview: Option<(PreviewRows, usize)>,
+  /// Row count above which unset sketches are used by default
+  exact_row_limit: Option<usize>,
+  low_memory: bool,
+  metadata: bool,
+  metadata_only: bool,
+}
+
+impl Default for Profiler {
+  fn default() -> Self {
+    Profiler {
+      columns: vec![],
+      exclude_columns: vec![],
+      filter: None,
+      group_by: None,
+      max_groups: 10,
+      categorical_threshold: 10,
+      percentiles: vec![],
+      quantile_method: QuantileMethod::Nearest,
+      quantile_sketch: None,
+      histogram: None,
+      approx_distinct: None,
+      heavy_hitters: None,
+      sample: None,
+      seed: 0,
+      preview: None,
+      exact_row_limit: Some(DEFAULT_EXACT_ROW_LIMIT),
+      low_memory: false,
+      metadata: false,
+      metadata_only: false,
+    }
+  }
+}
+
+impl Profiler {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Columns to analyze, by name or `^...$` regex. Defaults to every column.
+  pub fn columns<I, S>(mut self, columns: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.columns = columns.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Columns to skip, by name or `^...$` regex.
+  pub fn exclude_columns<I, S>(mut self, columns: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self.exclude_columns = columns.into_iter().map(Into::into).collect();
+    self
+  }
+
+  /// Only analyze rows matching this SQL predicate.
+  pub fn filter(mut self, predicate: impl Into<String>) -> Self {
+    self.filter = Some(predicate.into());
+    self
+  }
+
+  /// Summarize every other column separately for each value of `key`,
+  /// keeping the `max_groups` largest groups.
+  pub fn group_by(mut self, key: impl Into<String>, max_groups: usize) -> Self {
+    self.group_by = Some(key.into());
+    self.max_groups = max_groups;
+    self
+  }
+
+  /// Maximum number of distinct values to list a column's frequency table.
+  pub fn categorical_threshold(mut self, threshold: usize) -> Self {
+    self.categorical_threshold = threshold;
+    self
+  }
+
+  /// Additional percentiles (0-100) to report for numerical columns.
+  pub fn percentiles(mut self, percentiles: impl IntoIterator<Item = f64>) -> Self {
+    self.percentiles = percentiles.into_iter().collect();
+    self
+  }
+
+  /// Interpolation method used for quartiles, median, and percentiles.
+  pub fn quantile_method(mut self, method: QuantileMethod) -> Self {
+    self.quantile_method = method;
+    self
+  }
+
+  /// Estimate the median, quartiles, and percentiles with a KLL sketch of
+  /// size `k` that consumes each column batch by batch, instead of sorting
+  /// the whole column in memory. The quantile method is ignored.
+  pub fn approx_quantiles(mut self, k: usize) -> Self {
+    self.quantile_sketch = Some(k);
+    self
+  }
+
+  /// Include a histogram of every numerical column.
+  pub fn histogram(mut self, strategy: BinStrategy, bins: usize) -> Self {
+    self.histogram = Some((strategy, bins));
+    self
+  }
+
+  /// Estimate distinct counts with a HyperLogLog sketch whose relative
+  /// standard error is at most `error` percent, instead of counting every
+  /// distinct value exactly. Frequency tables are still exact for columns
+  /// that are clearly within the categorical threshold.
+  pub fn approx_distinct(mut self, error: f64) -> Self {
+    self.approx_distinct = Some(error);
+    self
+  }
+
+  /// List the `top_n` most frequent values of high-cardinality columns from
+  /// a Space-Saving summary with `counters` counters, instead of counting
+  /// every distinct value exactly. Each count overstates by at most the
+  /// number of values divided by `counters`. Distinct counts of these
+  /// columns are estimated as with [`Profiler::approx_distinct`], at its
+  /// default error unless set.
+  pub fn heavy_hitters(mut self, top_n: usize, counters: usize) -> Self {
+    self.heavy_hitters = Some((top_n, counters));
+    self
+  }
+
+  /// Profile a reproducible sample of the rows, drawn with `seed`, instead
+  /// of all of them. Means and proportions are reported with confidence
+  /// intervals. Row group samples only read the chosen row groups, so they
+  /// are only available when profiling parquet files.
+  pub fn sample(mut self, sample: Sample, seed: u64) -> Self {
+    self.sample = Some(sample);
+    self.seed = seed;
+    self
+  }
+
+  /// Show `count` example rows of the selected columns next to the
+  /// statistics, drawn from the rows being profiled.
+  pub fn preview(mut self, rows: PreviewRows, count: usize) -> Self {
+    self.preview = Some((rows, count));
+    self
+  }
+
+  /// Above `limit` rows, estimate the median, quartiles, percentiles,
+  /// distinct counts, and frequency tables with their sketches at the
+  /// default sizes, unless set, since computing them exactly holds a whole
+  /// column or every distinct value in memory. `None` always computes them
+  /// exactly.
+  pub fn exact_row_limit(mut self, limit: Option<usize>) -> Self {
+    self.exact_row_limit = limit;
+    self
+  }
+
+  /// Scan with reduced memory usage (limits parallelism).
+  pub fn low_memory(mut self, low_memory: bool) -> Self {
+    self.low_memory = low_memory;
+    self
+  }
+
+  /// Include the Parquet footer metadata of every file.
+  pub fn metadata(mut self, metadata: bool) -> Self {
+    self.metadata = metadata;
+    self
+  }
+
+  /// Summarize columns from Parquet footer statistics only, without reading
+  /// any data pages.
+  pub fn metadata_only(mut self, metadata_only: bool) -> Self {
+    self.metadata_only = metadata_only;
+    self
+  }
+
+  fn validate(&self) -> Result<()> {
+    if let Some(p) = self
+      .percentiles
+      .iter()
+      .find(|p| !(0.0..=100.0).contains(*p))
+    {
+      anyhow::bail!("Percentile {p} is out of range (expected 0-100)");
+    }
+    if let Some((_, 0)) = self.histogram {
+      anyhow::bail!("Histograms need at least one bin");
+    }
+    if let Some(k) = self.quantile_sketch
+      && k < kll::MIN_K
+    {
+      anyhow::bail!(
+        "Quantile sketch size {k} is too small (expected at least {})",
+        kll::MIN_K
+      );
+    }
+    if let Some((top_n, counters)) = self.heavy_hitters
+      && (top_n == 0 || counters < top_n)
+    {
+      anyhow::bail!("Heavy hitters need at least one top value and as many counters");
+    }
+    if let Some(error) = self.approx_distinct
+      && !(error > 0.0 && error < 100.0)
+    {
+      anyhow::bail!("Distinct count error {error} is out of range (expected 0-100 percent)");
+    }
+    match self.sample {
+      Some(Sample::Rows(0)) | Some(Sample::RowGroups(0)) => {
+        anyhow::bail!("A sample needs at least one row or row group")
+      }
+      Some(Sample::Fraction(fraction)) if !(fraction > 0.0 && fraction <= 1.0) => {
+        anyhow::bail!("Sample fraction {fraction} is out of range (expected 0-1)")
+      }
+      _ => {}
+    }
+    if self.metadata_only && (self.filter.is_some() || self.group_by.is_some()) {
+      anyhow::bail!("Footer statistics cannot be filtered or grouped");
+    }
+    if self.metadata_only && self.sample.is_some() {
+      anyhow::bail!("Footer statistics cannot be sampled");
+    }
+    if let Some((_, 0)) = self.preview {
+      anyhow::bail!("A preview needs at least one row");
+    }
+    if self.metadata_only && self.preview.is_some() {
+      anyhow::bail!("Footer statistics come without rows to preview");
+    }
+    Ok(())
+  }
+
+  /// Profiles a parquet file, a directory of parquet files, or a glob
+  /// pattern read as one dataset.
+  pub fn profile_path(&self, input: impl AsRef<Path>) -> Result<ParquetSummary> {
+    let input = input.as_ref();
+    self.validate()?;
+    validate_input(input)?;
+
+    let paths = resolve_input_files(input)?;
+    let files = paths
+      .iter()
+      .map(|path| {
+        Ok(FileSummary {
+          path: path.display().to_string(),
+          n_rows: file_row_count(path)?,
+        })
+      })
+      .collect::<Result<Vec<_>>>()?;
+
+    let metadata = if self.metadata {
+      Some(
+        paths
+          .iter()
+          .map(|path| metadata::read_footer(path))
+          .collect::<Result<Vec<_>>>()?,
+      )
+    } else {
+      None
+    };
+
+    if self.metadata_only {
+      let n_rows = files.iter().map(|file| file.n_rows).sum();
+      let mut columns = metadata::summarize_from_footers(&paths)?;
+      if is_dataset(input) {
+        let schema = self.scan_parquet(input)?.collect_schema()?;
+        let mut partitions = metadata::summarize_partitions(&paths, &schema, n_rows)?;
+        // Keys also stored in the files already have footer statistics
+        partitions.retain(|partition| columns.iter().all(|column| column.name != partition.name));
+        columns.extend(partitions);
+      }
+      let selection = ColumnSelection::new(&self.columns, &self.exclude_columns)?;
+      if !selection.is_all() {
+        let names = columns
+          .iter()
+          .map(|column| column.name.as_str())
+          .collect::<Vec<_>>();
+        let selected = selection
+          .select(&names)?
+          .into_iter()
+          .map(str::to_string)
+          .collect::<Vec<_>>();
+        columns.retain(|column| selected.contains(&column.name));
+      }
+      return Ok(ParquetSummary {
+        schema_version: SCHEMA_VERSION,
+        file: input.display().to_string(),
+        filter: None,
+        sample: None,
+        n_rows,
+        n_columns: columns.len(),
+        files,
+        metadata,
+        preview: None,
+        columns,
+        group_by: None,
+        baseline_check: None,
+        rules: None,
+      });
+    }
+
+    let (lazy_frame, sample) = self.sampled_scan(input)?;
+    let mut summary = self.summarize(lazy_frame, sample)?;
+    summary.file = input.display().to_string();
+    summary.files = files;
+    summary.metadata = metadata;
+    Ok(summary)
+  }
+
+  /// Profiles a lazy query, e.g. one scanning another format. The filter is
+  /// applied on top of it.
+  pub fn profile_lazy(&self, lazy_frame: LazyFrame) -> Result<ParquetSummary> {
+    self.validate()?;
+    if self.metadata || self.metadata_only {
+      anyhow::bail!("Footer metadata is only available when profiling parquet files");
+    }
+    if let Some(Sample::RowGroups(_)) = self.sample {
+      anyhow::bail!("Row group samples are only available when profiling parquet files");
+    }
+    let (lazy_frame, fraction) = self.sample_rows(self.prepare(lazy_frame)?)?;
+    self.summarize(lazy_frame, self.sample_summary(fraction))
+  }
+
+  /// Profiles a materialized frame.
+  pub fn profile_frame(&self, data_frame: &DataFrame) -> Result<ParquetSummary> {
+    self.profile_lazy(data_frame.clone().lazy())
+  }
+
+  /// Lazily scans the input with the filter applied, so it can be pushed
+  /// down into the parquet reader. Struct columns come out flattened into
+  /// dotted columns such as `payload.user_id`. Only the sampled rows are
+  /// scanned when a sample is set.
+  pub fn scan(&self, input: impl AsRef<Path>) -> Result<LazyFrame> {
+    Ok(self.sampled_scan(input)?.0)
+  }
+
+  /// Scans the input as [`Profiler::scan`] does, along with how it was
+  /// sampled.
+  fn sampled_scan(&self, input: impl AsRef<Path>) -> Result<(LazyFrame, Option<SampleSummary>)> {
+    let input = input.as_ref();
+    let scan = || self.scan_parquet(input);
+
+    // Row groups are drawn before the filter, so unsampled ones are never read
+    let (lazy_frame, fraction) = match self.sample {
+      Some(Sample::RowGroups(row_groups)) => {
+        let paths = resolve_input_files(input)?;
+        let (lazy_frame, fraction) =
+          sampling::sample_row_groups(&paths, row_groups, self.seed, scan)?;
+        (self.prepare(lazy_frame)?, Some(fraction))
+      }
+      _ => self.sample_rows(self.prepare(scan()?)?)?,
+    };
+    Ok((lazy_frame, self.sample_summary(fraction)))
+  }
+
+  /// Lazily scans the input as it is stored, with Hive partition columns
+  /// for directories and globs.
+  fn scan_parquet(&self, input: &Path) -> Result<LazyFrame> {
+    // Use lazy loading for efficiency with large files
+    let mut scan_args = ScanArgsParquet::default();
+    if self.low_memory {
+      scan_args.low_memory = true;
+    }
+    if is_dataset(input) {
+      scan_args.hive_options.enabled = Some(true);
+    }
+
+    LazyFrame::scan_parquet(input, scan_args)
+      .with_context(|| format!("Failed to scan parquet input '{}'", input.display()))
+  }
+
+  /// Draws a sample of the rows matching the filter, returning it with the
+  /// share of rows drawn. Row group samples are drawn by the scan instead.
+  fn sample_rows(&self, lazy_frame: LazyFrame) -> Result<(LazyFrame, Option<f64>)> {
+    match self.sample {
+      Some(Sample::Rows(rows)) => {
+        let (lazy_frame, fraction) = sampling::sample_rows(&lazy_frame, rows, self.seed)?;
+        Ok((lazy_frame, Some(fraction)))
+      }
+      Some(Sample::Fraction(fraction)) => Ok((
+        sampling::sample_fraction(lazy_frame, fraction, self.seed),
+        Some(fraction),
+      )),
+      Some(Sample::RowGroups(_)) | None => Ok((lazy_frame, None)),
+    }
+  }
+
+  fn sample_summary(&self, fraction: Option<f64>) -> Option<SampleSummary> {
+    Some(SampleSummary {
+      sample: self.sample?,
+      seed: self.seed,
+      fraction: fraction?,
+      confidence_level: sampling::CONFIDENCE_LEVEL,
+    })
+  }
+
+  /// Applies the filter, then flattens struct columns into dotted columns so
+  /// every field is profiled on its own.
+  fn prepare(&self, mut lazy_frame: LazyFrame) -> Result<LazyFrame> {
+    if let Some(filter) = &self.filter {
+      let predicate = polars::sql::sql_expr(filter)
+        .with_context(|| format!("Invalid --where predicate '{filter}'"))?;
+      lazy_frame = lazy_frame.filter(predicate);
+    }
+    flatten_structs(lazy_frame)
+  }
+
+  /// The options to profile `lazy_frame` with: past the exact row limit,
+  /// every sketch left unset is turned on at its default size.
+  fn bounded(&self, lazy_frame: &LazyFrame) -> Result<Profiler> {
+    let mut profiler = self.clone();
+    let Some(limit) = self.exact_row_limit else {
+      return Ok(profiler);
+    };
+    if self.quantile_sketch.is_some() && self.heavy_hitters.is_some() {
+      return Ok(profiler);
+    }
+
+    let rows = collect_streaming(lazy_frame.clone().select([len().alias(ROW_COUNT)]))
+      .with_context(|| "Failed to count rows")?;
+    if stat_usize(&rows, ROW_COUNT)? > limit {
+      profiler.quantile_sketch.get_or_insert(DEFAULT_SKETCH_SIZE);
+      profiler
+        .approx_distinct
+        .get_or_insert(DEFAULT_DISTINCT_ERROR);
+      profiler
+        .heavy_hitters
+        .get_or_insert((DEFAULT_TOP_VALUES, DEFAULT_HEAVY_HITTER_COUNTERS));
+    }
+    Ok(profiler)
+  }
+
+  /// Selects the columns and profiles them, split by the group-by key when
+  /// one is set.
+  fn summarize(
+    &self,
+    mut lazy_frame: LazyFrame,
+    sample: Option<SampleSummary>,
+  ) -> Result<ParquetSummary> {
+    let schema = lazy_frame
+      .collect_schema()
+      .with_context(|| "Failed to read parquet schema")?;
+
+    let mut columns = schema.iter_names().cloned().collect::<Vec<_>>();
+    let selection = ColumnSelection::new(&self.columns, &self.exclude_columns)?;
+    if !selection.is_all() {
+      let names = columns.iter().map(|name| name.as_str()).collect::<Vec<_>>();
+      columns = selection
+        .select(&names)?
+        .into_iter()
+        .map(PlSmallStr::from)
+        .collect();
+    }
+
+    let preview = match self.preview {
+      Some((rows, count)) => Some(preview::preview_rows(
+        &project(&lazy_frame, &columns),
+        rows,
+        count,
+      )?),
+      None => None,
+    };
+
+    let profiler = self.bounded(&lazy_frame)?;
+
+    // The key is what splits the groups, so it is not profiled itself
+    let mut group_by = match &self.group_by {
+      Some(key) => {
+        let key_type = schema
+          .get(key.as_str())
+          .with_context(|| format!("Group-by column '{key}' not found"))?
+          .clone();
+        columns.retain(|name| name != key);
+        Some(groups::summarize_groups(
+          &lazy_frame,
+          key,
+          &key_type,
+          &columns,
+          &profiler,
+        )?)
+      }
+      None => None,
+    };
+
+    let (n_rows, mut summaries) = summarize_columns(project(&lazy_frame, &columns), &profiler)?;
+
+    if let Some(sample) = &sample {
+      sampling::estimate_intervals(&mut summaries, n_rows, sample.fraction);
+      for group in group_by.iter_mut().flat_map(|grouped| &mut grouped.groups) {
+        sampling::estimate_intervals(&mut group.columns, group.n_rows, sample.fraction);
+      }
+    }
+
+    Ok(ParquetSummary {
+      schema_version: SCHEMA_VERSION,
+      file: IN_MEMORY.to_string(),
+      filter: self.filter.clone(),
+      sample,
+      n_rows,
+      n_columns: summaries.len(),
+      files: vec![],
+      metadata: None,
+      preview,
+      columns: summaries,
+      group_by,
+      baseline_check: None,
+      rules: None,
+    })
+  }
+}
+
+/// Validates the input exists (glob patterns are checked once expanded).
+fn validate_input(input: &Path) -> Result<()> {
+  if !input.exists() && !is_glob_pattern(input) {
+    anyhow::bail!("Input '{}' does not exist", input.display());
+  }
+  Ok(())
+}
+
+/// Replaces every struct column with its fields, recursively, named by their
+/// dotted path. Projection pushdown still limits the scan to the struct
+/// columns that end up being read.
+fn flatten_structs(mut lazy_frame: LazyFrame) -> Result<LazyFrame> {
+  let schema = lazy_frame
+    .collect_schema()
+    .with_context(|| "Failed to read parquet schema")?;
+  if !schema
+    .iter_values()
+    .any(|data_type| matches!(data_type, DataType::Struct(_)))
+  {
+    return Ok(lazy_frame);
+  }
+
+  let mut exprs = vec![];
+  for (name, data_type) in schema.iter() {
+    struct_fields(col(name.clone()), name, data_type, &mut exprs);
+  }
+  Ok(lazy_frame.select(exprs))
+}
+
+fn struct_fields(expr: Expr, name: &str, data_type: &DataType, exprs: &mut Vec<Expr>) {
+  match data_type {
+    DataType::Struct(fields) if !fields.is_empty() => {
+      for field in fields {
+        struct_fields(
+          expr.clone().struct_().field_by_name(field.name()),
+          &format!("{name}.{}", field.name()),
+          field.dtype(),
+          exprs,
+        );
+      }
+    }
+    _ => exprs.push(expr.alias(name)),
+  }
+}
+
+/// Projects the columns to analyze so the scan never reads the others.
+fn project(lazy_frame: &LazyFrame, columns: &[PlSmallStr]) -> LazyFrame {
+  lazy_frame.clone().select(
+    columns
+      .iter()
+      .map(|name| col(name.clone()))
+      .collect::<Vec<_>>(),
+  )
+}
+
+/// Profiles every column of `lazy_frame`, returning its row count along with
+/// the column summaries.
+fn summarize_columns(
+  mut lazy_frame: LazyFrame,
+  profiler: &Profiler,
+) -> Result<(usize, Vec<ColumnSummary>)> {
+  let schema = lazy_frame
+    .collect_schema()
+    .with_context(|| "Failed to read parquet schema")?;
+
+  // Express every per-column aggregate as a single lazy plan so the streaming
+  // engine computes them in one pass instead of materializing each column
+  let stats = collect_streaming(
+    lazy_frame
+      .clone()
+      .select(statistics_exprs(&schema, profiler)),
+  )
+  .with_context(|| "Failed to compute column statistics")?;
+
+  summarize_from_stats(&lazy_frame, &schema, &stats, profiler)
+}
+
+/// The row count and the aggregates of every column in `schema`, which
+/// `summarize_from_stats` reads back from the first row of their result.
+fn statistics_exprs(schema: &Schema, profiler: &Profiler) -> Vec<Expr> {
+  let mut exprs = vec![len().alias(ROW_COUNT)];
+  for (index, (name, data_type)) in schema.iter().enumerate() {
+    exprs.extend(aggregation_exprs(index, name, data_type, profiler));
+  }
+  exprs
+}
+
+/// Builds the column summaries from the aggregated statistics, running the
+/// passes that are not aggregates, such as frequency tables, over
+/// `lazy_frame`.
+fn summarize_from_stats(
+  lazy_frame: &LazyFrame,
+  schema: &Schema,
+  stats: &DataFrame,
+  profiler: &Profiler,
+) -> Result<(usize, Vec<ColumnSummary>)> {
+  let n_rows = stat_usize(stats, ROW_COUNT)?;
+
+  let mut summaries = Vec::new();
+
+  // Analyze each column
+  for (index, (name, data_type)) in schema.iter().enumerate() {
+    let null_count = n_rows - stat_usize(stats, &stat_name(index, "count"))?;
+    let summary = analyze_column(
+      lazy_frame, stats, index, name, data_type, null_count, profiler,
+    )
+    .with_context(|| format!("Failed to analyze column '{name}'"))?;
+
+    summaries.push(ColumnSummary {
+      name: name.to_string(),
+      data_type: format!("{data_type:?}"),
+      null_count: Some(null_count as u64),
+      null_percentage: percentage(null_count as u64, n_rows),
+      null_percentage_interval: None,
+      summary,
+    });
+  }
+
+  Ok((n_rows, summaries))
+}
+
+fn is_glob_pattern(path: &Path) -> bool {
+  path.to_string_lossy().contains(['*', '?', '['])
+}
+
+/// Directories and globs are read as one dataset, with Hive partitions.
+fn is_dataset(path: &Path) -> bool {
+  path.is_dir() || is_glob_pattern(path)
+}
+
+/// Lists the files behind the input using the same directory traversal and
+/// glob expansion as the parquet scan, so per-file results line up with it.
+fn resolve_input_files(input: &Path) -> Result<Vec<PathBuf>> {
+  let (paths, _) =
+    polars::io::path_utils::expand_paths_hive(&[input.to_path_buf()], true, None, true)
+      .with_context(|| format!("Failed to list files for '{}'", input.display()))?;
+
+  if paths.is_empty() {
+    anyhow::bail!("No parquet files found for '{}'", input.display());
+  }
+
+  Ok(paths.to_vec())
+}
+
+/// Reads the row count from a file's footer without touching its data pages.
+fn file_row_count(path: &Path) -> Result<usize> {
+  Ok(metadata::read_file_metadata(path)?.num_rows)
+}
+
+/// Name of the row count in the aggregated statistics frame.
+const ROW_COUNT: &str = "__rows";
+
+/// Relative standard error, in percent, of distinct count estimates made for
+/// heavy hitters when no error is set with [`Profiler::approx_distinct`].
+pub const DEFAULT_DISTINCT_ERROR: f64 = 1.0;
+
+/// Size of the KLL sketch used past the exact row limit.
+pub const DEFAULT_SKETCH_SIZE: usize = 200;
+
+/// Top values and Space-Saving counters used past the exact row limit.
+pub const DEFAULT_TOP_VALUES: usize = 10;
+pub const DEFAULT_HEAVY_HITTER_COUNTERS: usize = 1000;
+
+/// Rows above which the sketches are used unless set, see
+/// [`Profiler::exact_row_limit`].
+pub const DEFAULT_EXACT_ROW_LIMIT: usize = 10_000_000;
+
+/// Name of the count column produced when computing value frequencies.
+const COUNT_COLUMN: &str = "__count";
+
+/// Name of a single column of values taken out of a frame to work on alone.
+const VALUE_COLUMN: &str = "__value";
+
+/// How a column is profiled, decided from its dtype before any data is read.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum ColumnKind {
+  Numerical,
+  Boolean,
+  Categorical,
+  Temporal,
+  List,
+  Other,
+}
+
+fn column_kind(data_type: &DataType) -> ColumnKind {
+  match data_type {
+    // Numerical types
+    DataType::UInt8
+    | DataType::UInt16
+    | DataType::UInt32
+    | DataType::UInt64
+    | DataType::Int8
+    | DataType::Int16
+    | DataType::Int32
+    | DataType::Int64
+    | DataType::Int128
+    | DataType::Float32
+    | DataType::Float64
+    | DataType::Decimal(_, _) => ColumnKind::Numerical,
+
+    DataType::Boolean => ColumnKind::Boolean,
+
+    // String and categorical types
+    DataType::String | DataType::Categorical(_, _) | DataType::Enum(_, _) => {
+      ColumnKind::Categorical
+    }
+
+    // Temporal types
+    DataType::Date | DataType::Datetime(_, _) | DataType::Duration(_) | DataType::Time => {
+      ColumnKind::Temporal
+    }
+
+    // Nested types (struct columns are flattened before they get here)
+    DataType::List(_) | DataType::Array(_, _) => ColumnKind::List,
+
+    _ => ColumnKind::Other,
+  }
+}
+
+fn percentage(count: u64, total: usize) -> Option<f64> {
+  (total > 0).then(|| count as f64 / total as f64 * 100.0)
+}
+
+/// Alias of a per-column aggregate in the statistics frame. Columns are keyed
+/// by position so arbitrary column names cannot collide with each other.
+fn stat_name(index: usize, stat: &str) -> String {
+  format!("{index}:{stat}")
+}
+
+/// Aggregate expressions contributed by one column to the statistics plan.
+fn aggregation_exprs(
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  profiler: &Profiler,
+) -> Vec<Expr> {
+  let column = col(name);
+  let method = profiler.quantile_method;
+
+  // Non-null count, from which the null count is derived
+  let mut exprs = vec![column.clone().count().alias(stat_name(index, "count"))];
+
+  if data_type.is_float() {
+    exprs.push(column.clone().is_nan().sum().alias(stat_name(index, "nan")));
+    exprs.push(
+      column
+        .clone()
+        .is_infinite()
+        .sum()
+        .alias(stat_name(index, "infinite")),
+    );
+  }
+
+  match column_kind(data_type) {
+    ColumnKind::Numerical => {
+      // Decimals get exact extremes and total, rendered by polars at their
+      // declared scale; everything else is computed on floats
+      let values = if matches!(data_type, DataType::Decimal(_, _)) {
+        exprs.extend([
+          column
+            .clone()
+            .min()
+            .cast(DataType::String)
+            .alias(stat_name(index, "exact_min")),
+          column
+            .clone()
+            .max()
+            .cast(DataType::String)
+            .alias(stat_name(index, "exact_max")),
+          column
+            .clone()
+            .sum()
+            .cast(DataType::String)
+            .alias(stat_name(index, "exact_sum")),
+        ]);
+        column.clone().cast(DataType::Float64)
+      } else {
+        column.clone()
+      };
+
+      exprs.extend([
+        values.clone().min().alias(stat_name(index, "min")),
+        values.clone().max().alias(stat_name(index, "max")),
+        values.clone().sum().alias(stat_name(index, "sum")),
+        values.clone().mean().alias(stat_name(index, "mean")),
+        values.clone().std(1).alias(stat_name(index, "std")),
+      ]);
+
+      // Exact quantiles need the whole column in memory; a sketch is built
+      // by a separate streaming pass instead
+      if profiler.quantile_sketch.is_some() {
+        return exprs;
+      }
+      // NaN and infinities would otherwise be ranked as values; they become
+      // nulls, which quantiles skip, as the sketch leaves them out too
+      let values = when(values.clone().is_finite())
+        .then(values)
+        .otherwise(lit(NULL));
+      exprs.extend([
+        values
+          .clone()
+          .quantile(lit(0.5), method)
+          .alias(stat_name(index, "median")),
+        values
+          .clone()
+          .quantile(lit(0.25), method)
+          .alias(stat_name(index, "q25")),
+        values
+          .clone()
+          .quantile(lit(0.75), method)
+          .alias(stat_name(index, "q75")),
+      ]);
+      exprs.extend(profiler.percentiles.iter().map(|p| {
+        values
+          .clone()
+          .quantile(lit(p / 100.0), method)
+          .alias(stat_name(index, &percentile_stat(*p)))
+      }));
+    }
+    ColumnKind::Boolean => exprs.push(column.clone().sum().alias(stat_name(index, "true"))),
+    ColumnKind::Temporal => exprs.extend(temporal::aggregation_exprs(index, name)),
+    ColumnKind::List => {
+      let lengths = list_lengths(name, data_type);
+      exprs.extend([
+        lengths.clone().min().alias(stat_name(index, "min_length")),
+        lengths.clone().max().alias(stat_name(index, "max_length")),
+        lengths
+          .clone()
+          .mean()
+          .alias(stat_name(index, "mean_length")),
+        lengths.eq(lit(0)).sum().alias(stat_name(index, "empty")),
+      ]);
+    }
+    // Distinct values are counted by a separate group-by over the non-null
+    // values, so only the null count and text statistics are needed here
+    ColumnKind::Categorical | ColumnKind::Other => {
+      exprs.extend(strings::aggregation_exprs(index, name, data_type))
+    }
+  }
+
+  exprs
+}
+
+fn percentile_stat(percentile: f64) -> String {
+  format!("p{percentile}")
+}
+
+/// Runs a lazy query on the streaming engine, which keeps memory bounded by
+/// processing the file in morsels rather than loading it up front.
+fn collect_streaming(lazy_frame: LazyFrame) -> PolarsResult<DataFrame> {
+  lazy_frame.collect_with_engine(Engine::Streaming)
+}
+
+/// An aggregation that hands every batch of `expr` to `sketch` on the
+/// streaming engine and merges the result into `shared`, so sketches are
+/// built morsel by morsel without the column being gathered in one place.
+fn sketch_batches<S: Send + 'static>(
+  expr: Expr,
+  shared: &Arc<Mutex<S>>,
+  sketch: impl Fn(&Column) -> PolarsResult<S> + Send + Sync + 'static,
+  merge: fn(&mut S, &S),
+) -> Expr {
+  let shared = Arc::clone(shared);
+  let feed = move |column: Column| {
+    let batch = sketch(&column)?;
+    let mut shared = shared
+      .lock()
+      .map_err(|_| polars_err!(ComputeError: "sketch lock poisoned"))?;
+    merge(&mut shared, &batch);
+    // The map is elementwise, so it hands back a column of the same length
+    Ok(Some(Column::new_scalar(
+      column.name().clone(),
+      Scalar::from(true),
+      column.len(),
+    )))
+  };
+  expr
+    .map(feed, GetOutput::from_type(DataType::Boolean))
+    .sum()
+}
+
+fn stat_f64(stats: &DataFrame, index: usize, stat: &str) -> Option<f64> {
+  stats
+    .column(&stat_name(index, stat))
+    .ok()?
+    .get(0)
+    .ok()?
+    .extract::<f64>()
+}
+
+fn stat_string(stats: &DataFrame, index: usize, stat: &str) -> Option<String> {
+  let value = stats.column(&stat_name(index, stat)).ok()?.get(0).ok()?;
+  value.get_str().map(str::to_string)
+}
+
+fn stat_usize(stats: &DataFrame, name: &str) -> Result<usize> {
+  stats
+    .column(name)
+    .ok()
+    .and_then(|column| column.get(0).ok())
+    .and_then(|value| value.extract::<usize>())
+    .ok_or_else(|| anyhow::anyhow!("Missing '{}' statistic", name))
+}
+
+fn analyze_column(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  null_count: usize,
+  profiler: &Profiler,
+) -> Result<ColumnStats> {
+  match column_kind(data_type) {
+    ColumnKind::Numerical => {
+      analyze_numerical_column(lazy_frame, stats, index, name, data_type, profiler)
+    }
+    ColumnKind::Boolean => analyze_boolean_column(stats, index, null_count),
+    ColumnKind::Temporal => {
+      temporal::analyze_temporal_column(lazy_frame, stats, index, name, data_type)
+    }
+    ColumnKind::List => analyze_list_column(lazy_frame, stats, index, name, data_type, profiler),
+    ColumnKind::Categorical | ColumnKind::Other => analyze_frequencies(
+      lazy_frame, stats, index, name, data_type, null_count, profiler,
+    ),
+  }
+}
+
+/// Frequency tables of categorical columns and of any other type whose
+/// values can be counted, estimated from sketches when asked for.
+fn analyze_frequencies(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  null_count: usize,
+  profiler: &Profiler,
+) -> Result<ColumnStats> {
+  // Sketches are asked for when columns are too large to count exactly, so
+  // their patterns are not counted either
+  let strings = strings::analyze_string_column(
+    lazy_frame,
+    stats,
+    index,
+    name,
+    data_type,
+    profiler.heavy_hitters.is_none() && profiler.approx_distinct.is_none(),
+  )?;
+
+  // Columns that are clearly over the threshold report the sketch's estimate
+  // and skip the exact count, which hashes every distinct value
+  if profiler.approx_distinct.is_some() || profiler.heavy_hitters.is_some() {
+    let error = profiler.approx_distinct.unwrap_or(DEFAULT_DISTINCT_ERROR);
+    let precision = HyperLogLog::with_error(error / 100.0).precision();
+    let sketch = hyperloglog::sketch_column(lazy_frame, name, precision)
+      .with_context(|| format!("Failed to estimate distinct values of '{name}'"))?;
+    let estimate = sketch.estimate();
+    if estimate * (1.0 - 3.0 * sketch.relative_error()) > profiler.categorical_threshold as f64 {
+      let (frequency_table, frequency_error) = match profiler.heavy_hitters {
+        Some((top_n, counters)) => {
+          let summary = space_saving::sketch_column(lazy_frame, name, counters)?;
+          let top = summary.top(top_n);
+          let error = top.iter().map(|hitter| hitter.error).max().unwrap_or(0);
+          let mut frequency_table = top
+            .into_iter()
+            .map(|hitter| (hitter.value, hitter.count))
+            .collect();
+          rank_nulls(&mut frequency_table, null_count, top_n);
+          (frequency_table, Some(error))
+        }
+        None => (vec![], None),
+      };
+      return Ok(ColumnStats::Categorical {
+        showing_top_n: !frequency_table.is_empty(),
+        frequency_table,
+        total_unique: estimate.round() as usize + usize::from(null_count > 0),
+        distinct_error: Some(sketch.relative_error() * 100.0),
+        frequency_error,
+        strings,
+      });
+    }
+  }
+
+  let counts = value_counts(lazy_frame, name)?;
+  let unique_count = counts.height() + usize::from(null_count > 0);
+
+  // For other types, treat as categorical if they have reasonable number of unique values
+  if column_kind(data_type) == ColumnKind::Other && unique_count > profiler.categorical_threshold {
+    // For complex types with too many unique values, just show basic info
+    return Ok(ColumnStats::Categorical {
+      frequency_table: vec![],
+      total_unique: unique_count,
+      distinct_error: None,
+      frequency_error: None,
+      showing_top_n: false,
+      strings,
+    });
+  }
+
+  analyze_categorical_column(
+    &counts,
+    name,
+    null_count,
+    unique_count,
+    profiler.categorical_threshold,
+    strings,
+  )
+}
+
+fn analyze_numerical_column(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  profiler: &Profiler,
+) -> Result<ColumnStats> {
+  let nan_count = stat_f64(stats, index, "nan").map(|count| count as u64);
+  let infinite_count = stat_f64(stats, index, "infinite").map(|count| count as u64);
+  let min = stat_f64(stats, index, "min");
+  let max = stat_f64(stats, index, "max");
+  let sum = stat_f64(stats, index, "sum");
+  let mean = stat_f64(stats, index, "mean");
+  let std_dev = stat_f64(stats, index, "std");
+
+  let sketch = match profiler.quantile_sketch {
+    Some(k) => Some(kll::sketch_column(lazy_frame, name, k)?),
+    None => None,
+  };
+  let quantile = |quantile: f64, stat: &str| match &sketch {
+    Some(sketch) => sketch.quantile(quantile),
+    None => stat_f64(stats, index, stat),
+  };
+  let median = quantile(0.5, "median");
+  let q25 = quantile(0.25, "q25");
+  let q75 = quantile(0.75, "q75");
+
+  let iqr = match (q25, q75) {
+    (Some(q25_val), Some(q75_val)) => Some(q75_val - q25_val),
+    _ => None,
+  };
+
+  let percentiles = profiler
+    .percentiles
+    .iter()
+    .map(|p| Percentile {
+      percentile: *p,
+      value: quantile(p / 100.0, &percentile_stat(*p)),
+    })
+    .collect();
+
+  let histogram = match profiler.histogram {
+    Some((strategy, bins)) => {
+      histogram::compute_histogram(lazy_frame, name, strategy, bins, profiler)?
+    }
+    None => None,
+  };
+
+  let decimal = match data_type {
+    DataType::Decimal(precision, scale) => Some(DecimalStats {
+      precision: *precision,
+      scale: scale.unwrap_or(0),
+      min: stat_string(stats, index, "exact_min"),
+      max: stat_string(stats, index, "exact_max"),
+      sum: stat_string(stats, index, "exact_sum"),
+    }),
+    _ => None,
+  };
+
+  Ok(ColumnStats::Numerical {
+    nan_count,
+    infinite_count,
+    min,
+    max,
+    sum,
+    mean,
+    mean_interval: None,
+    std_dev,
+    median,
+    q25,
+    q75,
+    iqr,
+    percentiles,
+    histogram,
+    decimal,
+    quantile_rank_error: sketch.map(|sketch| sketch.rank_error() * 100.0),
+  })
+}
+
+fn analyze_boolean_column(
+  stats: &DataFrame,
+  index: usize,
+  null_count: usize,
+) -> Result<ColumnStats> {
+  let non_null_count = stat_usize(stats, &stat_name(index, "count"))?;
+  let n_rows = non_null_count + null_count;
+  let true_count = stat_f64(stats, index, "true").map_or(0, |count| count as u64);
+  let false_count = non_null_count as u64 - true_count;
+
+  Ok(ColumnStats::Boolean {
+    true_count,
+    false_count,
+    true_percentage: percentage(true_count, n_rows),
+    false_percentage: percentage(false_count, n_rows),
+    true_percentage_interval: None,
+    false_percentage_interval: None,
+  })
+}
+
+fn list_lengths(name: &str, data_type: &DataType) -> Expr {
+  match data_type {
+    DataType::Array(_, _) => col(name).arr().len(),
+    _ => col(name).list().len(),
+  }
+}
+
+/// Reports list lengths, then profiles the exploded elements as a column of
+/// their own, recursing into lists of lists and lists of structs.
+fn analyze_list_column(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  profiler: &Profiler,
+) -> Result<ColumnStats> {
+  let n_lists = stat_usize(stats, &stat_name(index, "count"))?;
+  let empty_count = stat_f64(stats, index, "empty").map_or(0, |count| count as u64);
+
+  // Empty lists explode into a null row, so they are dropped beforehand and
+  // element nulls are only the nulls stored inside lists
+  let element = match data_type {
+    DataType::Array(_, _) => col(name).arr().explode(),
+    _ => col(name).explode(),
+  };
+  let elements = lazy_frame
+    .clone()
+    .select([col(name)])
+    .filter(list_lengths(name, data_type).gt(lit(0)))
+    .select([element.alias(format!("{name}[]"))]);
+  let (element_count, elements) = summarize_columns(flatten_structs(elements)?, profiler)
+    .with_context(|| format!("Failed to analyze the elements of '{name}'"))?;
+
+  Ok(ColumnStats::List {
+    min_length: stat_f64(stats, index, "min_length").map(|length| length as u64),
+    max_length: stat_f64(stats, index, "max_length").map(|length| length as u64),
+    mean_length: stat_f64(stats, index, "mean_length"),
+    empty_count,
+    empty_percentage: percentage(empty_count, n_lists),
+    element_count: element_count as u64,
+    elements,
+  })
+}
+
+/// Counts occurrences of every non-null value with a streaming group-by.
+fn value_counts(lazy_frame: &LazyFrame, name: &str) -> Result<DataFrame> {
+  collect_streaming(count_values(lazy_frame, col(name), name))
+    .with_context(|| "Failed to count distinct values")
+}
+
+/// The query behind [`value_counts`], counting the values of `value` into a
+/// column `name` next to [`COUNT_COLUMN`].
+///
+/// Nulls are filtered out first and accounted for separately: the streaming
+/// group-by in polars 0.49 can index out of bounds when null keys share its
+/// hot table with a full set of regular keys.
+fn count_values(lazy_frame: &LazyFrame, value: Expr, name: &str) -> LazyFrame {
+  lazy_frame
+    .clone()
+    .select([value.alias(name)])
+    .filter(col(name).is_not_null())
+    .group_by([col(name)])
+    .agg([len().alias(COUNT_COLUMN)])
+}
+
+fn analyze_categorical_column(
+  counts: &DataFrame,
+  name: &str,
+  null_count: usize,
+  unique_count: usize,
+  categorical_threshold: usize,
+  strings: Option<StringStats>,
+) -> Result<ColumnStats> {
+  let showing_top_n = unique_count > categorical_threshold;
+  let limit = if showing_top_n {
+    std::cmp::min(10, unique_count)
+  } else {
+    unique_count
+  };
+
+  // Keep only the most frequent values, breaking ties by value for stable output
+  let top_counts = match counts.sort(
+    [COUNT_COLUMN, name],
+    SortMultipleOptions::default().with_order_descending_multi([true, false]),
+  ) {
+    Ok(sorted) => sorted.head(Some(limit)),
+    Err(_) => {
+      // Fallback: just return unique count
+      return Ok(ColumnStats::Categorical {
+        frequency_table: vec![],
+        total_unique: unique_count,
+        distinct_error: None,
+        frequency_error: None,
+        showing_top_n: false,
+        strings,
+      });
+    }
+  };
+
+  let mut frequency_table = Vec::new();
+
+  // Extract the values and counts
+  let values_column = top_counts
+    .column(name)
+    .map_err(|e| anyhow::anyhow!("Failed to get values column: {}", e))?;
+  let counts_column = top_counts
+    .column(COUNT_COLUMN)
+    .map_err(|e| anyhow::anyhow!("Failed to get counts column: {}", e))?;
+
+  for i in 0..top_counts.height() {
+    let value = values_column
+      .get(i)
+      .map_err(|e| anyhow::anyhow!("Failed to get value at index {}: {}", i, e))?;
+    let count = counts_column
+      .get(i)
+      .map_err(|e| anyhow::anyhow!("Failed to get count at index {}: {}", i, e))?;
+
+    let value_str = match value.get_str() {
+      Some(s) => s.to_string(),
+      None => format!("{value}"),
+    };
+
+    if let Some(count_val) = count.extract::<u64>() {
+      frequency_table.push((value_str, count_val));
+    }
+  }
+
+  rank_nulls(&mut frequency_table, null_count, limit);
+
+  Ok(ColumnStats::Categorical {
+    frequency_table,
+    total_unique: unique_count,
+    distinct_error: None,
+    frequency_error: None,
+    showing_top_n,
+    strings,
+  })
+}
+
+/// Nulls count as a distinct value, ranked alongside the others in a
+/// frequency table of at most `limit` entries.
+fn rank_nulls(frequency_table: &mut Vec<(String, u64)>, null_count: usize, limit: usize) {
+  let null_count = null_count as u64;
+  if null_count > 0 {
+    let position = frequency_table
+      .iter()
+      .position(|(_, count)| *count < null_count)
+      .unwrap_or(frequency_table.len());
+    frequency_table.insert(position, ("null".to_string(), null_count));
+    frequency_table.truncate(limit);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::{TempDir, expect_categorical, expect_numerical, expect_stats};
+
+  #[test]
+  fn profiles_zero_rows_with_a_string_column() {
+    let frame = df!(
+      "id" => Vec::<i64>::new(),
+      "value" => Vec::<String>::new(),
+    )
+    .unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+
+    assert_eq!(summary.n_rows, 0);
+    assert_eq!(summary.columns.len(), 2);
+  }
+
+  #[test]
+  fn profiles_zero_rows_with_a_binary_column() {
+    let frame =
+      DataFrame::new(vec![Column::new_empty("payload".into(), &DataType::Binary)]).unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+
+    assert_eq!(summary.n_rows, 0);
+  }
+
+  #[test]
+  fn ranks_nulls_beyond_u32_counts() {
+    let large = u64::from(u32::MAX) + 10;
+    let mut frequency_table = vec![("a".to_string(), large + 1), ("b".to_string(), 3)];
+    rank_nulls(&mut frequency_table, large as usize, 2);
+
+    assert_eq!(
+      frequency_table,
+      vec![("a".to_string(), large + 1), ("null".to_string(), large)]
+    );
+  }
+
+  #[test]
+  fn leaves_non_finite_values_out_of_exact_quantiles() {
+    let frame =
+      df!("value" => [1.0, f64::NAN, f64::INFINITY, 2.0, 3.0, f64::NEG_INFINITY]).unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+
+    expect_numerical!(summary.columns[0], { median, q25, q75, .. });
+    assert_eq!(*median, Some(2.0));
+    for quartile in [q25, q75] {
+      assert!(quartile.is_some_and(|quartile| (1.0..=3.0).contains(&quartile)));
+    }
+  }
+
+  #[test]
+  fn filters_rows_before_summarizing() {
+    let frame = df!(
+      "amount" => [5.0, -1.0, 3.0, -2.0],
+      "country" => ["KR", "KR", "US", "KR"],
+    )
+    .unwrap();
+
+    let profiler = Profiler::new().filter("country = 'KR' AND amount > 0");
+    let summary = profiler.profile_frame(&frame).unwrap();
+    assert_eq!(summary.n_rows, 1);
+    assert_eq!(
+      summary.filter.as_deref(),
+      Some("country = 'KR' AND amount > 0")
+    );
+    expect_numerical!(summary.columns[0], { min, max, .. });
+    assert_eq!((*min, *max), (Some(5.0), Some(5.0)));
+
+    let error = Profiler::new()
+      .filter("amount >")
+      .profile_frame(&frame)
+      .unwrap_err();
+    assert!(
+      error.to_string().starts_with("Invalid --where predicate"),
+      "{error}"
+    );
+  }
+
+  #[test]
+  fn flattens_structs_and_profiles_list_elements() {
+    let tags = Series::new(
+      "tags".into(),
+      [
+        Series::new("".into(), ["a", "b"]),
+        Series::new("".into(), Vec::<&str>::new()),
+        Series::new("".into(), ["a"]),
+        Series::new("".into(), ["c"]),
+      ],
+    );
+    let frame = df!("user_id" => [1i64, 2, 3, 4], "tags" => tags)
+      .unwrap()
+      .lazy()
+      .select([
+        as_struct(vec![col("user_id"), col("tags")]).alias("payload"),
+        col("user_id").alias("id"),
+      ])
+      .collect()
+      .unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+    let names = summary
+      .columns
+      .iter()
+      .map(|column| column.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, ["payload.user_id", "payload.tags", "id"]);
+
+    expect_stats!(
+      summary.columns[1],
+      List {
+        min_length,
+        max_length,
+        empty_count,
+        element_count,
+        elements,
+        ..
+      }
+    );
+    assert_eq!((*min_length, *max_length), (Some(0), Some(2)));
+    assert_eq!((*empty_count, *element_count), (1, 4));
+    assert_eq!(elements[0].name, "payload.tags[]");
+    expect_categorical!(elements[0], { frequency_table, .. });
+    assert_eq!(frequency_table[0], ("a".to_string(), 2));
+  }
+
+  #[test]
+  fn shares_boolean_counts_of_all_rows() {
+    let frame = df!("flag" => [Some(true), Some(false), None, Some(true)]).unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+    assert_eq!(summary.columns[0].null_percentage, Some(25.0));
+    expect_stats!(
+      summary.columns[0],
+      Boolean {
+        true_count,
+        false_count,
+        true_percentage,
+        false_percentage,
+        ..
+      }
+    );
+    assert_eq!((*true_count, *false_count), (2, 1));
+    assert_eq!(
+      (*true_percentage, *false_percentage),
+      (Some(50.0), Some(25.0))
+    );
+  }
+
+  #[test]
+  fn sums_decimals_exactly_at_their_scale() {
+    let frame = df!("price" => ["0.10", "0.20", "0.30", "-1.05"])
+      .unwrap()
+      .lazy()
+      .select([col("price").cast(DataType::Decimal(Some(10), Some(2)))])
+      .collect()
+      .unwrap();
+
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+    expect_numerical!(summary.columns[0], { decimal: Some(decimal), .. });
+    assert_eq!((decimal.precision, decimal.scale), (Some(10), 2));
+    assert_eq!(decimal.min.as_deref(), Some("-1.05"));
+    assert_eq!(decimal.max.as_deref(), Some("0.30"));
+    assert_eq!(decimal.sum.as_deref(), Some("-0.45"));
+  }
+
+  #[test]
+  fn serializes_a_versioned_json_document() {
+    let frame = df!(
+      "amount" => [1.5, 2.5],
+      "country" => ["KR", "KR"],
+    )
+    .unwrap();
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+    let json = serde_json::to_value(&summary).unwrap();
+
+    assert_eq!(json["schema_version"], SCHEMA_VERSION);
+    assert_eq!(json["file"], IN_MEMORY);
+    assert_eq!(json["n_rows"], 2);
+    assert_eq!(json["columns"][0]["name"], "amount");
+    assert_eq!(json["columns"][0]["summary"]["kind"], "numerical");
+    assert_eq!(json["columns"][0]["summary"]["mean"], 2.0);
+    assert_eq!(json["columns"][1]["summary"]["kind"], "categorical");
+    assert_eq!(
+      json["columns"][1]["summary"]["frequency_table"],
+      serde_json::json!([["KR", 2]])
+    );
+
+    // Column summaries round-trip, which baselines rely on
+    let columns: Vec<ColumnSummary> = serde_json::from_value(json["columns"].clone()).unwrap();
+    assert_eq!(columns.len(), 2);
+  }
+
+  #[test]
+  fn profiles_a_hive_partitioned_directory_as_one_dataset() {
+    let root = TempDir::new("hive");
+    for (year, amounts) in [("2023", vec![1.0, 2.0]), ("2024", vec![3.0, 4.0, 5.0])] {
+      let partition = root.join(format!("year={year}"));
+      std::fs::create_dir_all(&partition).unwrap();
+      let mut frame = df!("amount" => amounts).unwrap();
+      ParquetWriter::new(std::fs::File::create(partition.join("part-0.parquet")).unwrap())
+        .finish(&mut frame)
+        .unwrap();
+    }
+
+    let summary = Profiler::new().profile_path(root.path()).unwrap();
+    assert_eq!(summary.n_rows, 5);
+    let files = summary
+      .files
+      .iter()
+      .map(|file| (file.path.ends_with("year=2023/part-0.parquet"), file.n_rows))
+      .collect::<Vec<_>>();
+    assert_eq!(files, [(true, 2), (false, 3)]);
+    // The partition key becomes a column of its own
+    let names = summary
+      .columns
+      .iter()
+      .map(|column| column.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, ["amount", "year"]);
+    expect_numerical!(summary.columns[0], { mean, .. });
+    assert_eq!(*mean, Some(3.0));
+
+    let summary = Profiler::new()
+      .profile_path(root.join("year=2024/*.parquet"))
+      .unwrap();
+    assert_eq!(summary.n_rows, 3);
+    assert_eq!(summary.files.len(), 1);
+
+    // Footers do not store the key, so its statistics come from the paths
+    let summary = Profiler::new()
+      .metadata_only(true)
+      .profile_path(root.path())
+      .unwrap();
+    let year = &summary.columns[1];
+    assert_eq!(
+      (year.name.as_str(), year.data_type.as_str(), year.null_count),
+      ("year", "Int64", Some(0))
+    );
+    expect_stats!(
+      year,
+      Footer {
+        min,
+        max,
+        distinct_count,
+        ..
+      }
+    );
+    assert_eq!(
+      (min.as_deref(), max.as_deref(), *distinct_count),
+      (Some("2023"), Some("2024"), Some(2))
+    );
+  }
+
+  #[test]
+  fn rejects_missing_inputs() {
+    let root = TempDir::new("missing");
+    let error = Profiler::new()
+      .profile_path(root.join("missing.parquet"))
+      .unwrap_err();
+    assert!(error.to_string().contains("does not exist"), "{error}");
+
+    assert!(
+      Profiler::new()
+        .profile_path(root.join("*.parquet"))
+        .is_err()
+    );
+  }
+
+  #[test]
+  fn counts_nulls_in_every_column() {
+    let frame = df!(
+      "amount" => [Some(1.0), None, Some(f64::NAN), Some(f64::INFINITY)],
+      "country" => [Some("KR"), None, None, Some("US")],
+      "active" => [Some(true), Some(false), Some(true), Some(false)],
+      "missing" => [None::<i32>, None, None, None],
+    )
+    .unwrap();
+    let summary = Profiler::new().profile_frame(&frame).unwrap();
+
+    let nulls = summary
+      .columns
+      .iter()
+      .map(|column| (column.null_count, column.null_percentage))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      nulls,
+      [
+        (Some(1), Some(25.0)),
+        (Some(2), Some(50.0)),
+        (Some(0), Some(0.0)),
+        (Some(4), Some(100.0))
+      ]
+    );
+
+    // NaN and infinity are values, not nulls, and are counted on their own
+    expect_numerical!(summary.columns[0], { nan_count, infinite_count, .. });
+    assert_eq!((*nan_count, *infinite_count), (Some(1), Some(1)));
+    let output = report::format_summary(&summary);
+    assert!(output.contains("   Nulls: 2 (50.0%)\n"), "{output}");
+  }
+
+  #[test]
+  fn reports_how_many_top_values_are_shown() {
+    let frame = df!("grade" => ["A", "B", "C", "D", "E", "A"]).unwrap();
+    let summary = Profiler::new()
+      .categorical_threshold(3)
+      .profile_frame(&frame)
+      .unwrap();
+
+    let output = report::format_summary(&summary);
+    assert!(
+      output.contains("5 total unique values (showing top 5):\n"),
+      "{output}"
+    );
+  }
+
+  #[test]
+  fn computes_extremes_median_and_percentiles() {
+    let frame = df!("value" => (1..=101i64).rev().collect::<Vec<_>>()).unwrap();
+    let summary = Profiler::new()
+      .percentiles([10.0, 99.5])
+      .quantile_method(QuantileMethod::Linear)
+      .profile_frame(&frame)
+      .unwrap();
+
+    expect_numerical!(summary.columns[0], { min, max, median, q25, q75, iqr, percentiles, .. });
+    assert_eq!((*min, *max, *median), (Some(1.0), Some(101.0), Some(51.0)));
+    assert_eq!((*q25, *q75, *iqr), (Some(26.0), Some(76.0), Some(50.0)));
+    let percentiles = percentiles
+      .iter()
+      .map(|percentile| (percentile.percentile, percentile.value))
+      .collect::<Vec<_>>();
+    assert_eq!(percentiles, [(10.0, Some(11.0)), (99.5, Some(100.5))]);
+  }
+
+  #[test]
+  fn streams_the_same_statistics_as_an_in_memory_frame() {
+    let root = TempDir::new("streaming");
+    let path = root.join("streaming.parquet");
+    let mut frame = df!(
+      "amount" => (0..1000).map(|i| f64::from(i % 97) * 1.5).collect::<Vec<_>>(),
+      "country" => (0..1000).map(|i| ["KR", "US", "DE"][i % 3]).collect::<Vec<_>>(),
+      "active" => (0..1000).map(|i| i % 4 == 0).collect::<Vec<_>>(),
+    )
+    .unwrap();
+    ParquetWriter::new(std::fs::File::create(&path).unwrap())
+      .with_row_group_size(Some(100))
+      .finish(&mut frame)
+      .unwrap();
+    let streamed = Profiler::new().profile_path(&path).unwrap();
+    let in_memory = Profiler::new().profile_frame(&frame).unwrap();
+
+    assert_eq!(streamed.n_rows, 1000);
+    assert_eq!(
+      serde_json::to_value(&streamed.columns).unwrap(),
+      serde_json::to_value(&in_memory.columns).unwrap()
+    );
+  }
+
+  #[test]
+  fn sketches_inputs_past_the_exact_row_limit() {
+    let frame = df!(
+      "value" => (0..40).map(f64::from).collect::<Vec<_>>(),
+      "id" => (0..40).map(|i| format!("id-{i}")).collect::<Vec<_>>(),
+    )
+    .unwrap();
+
+    for (limit, sketched) in [(Some(39), true), (Some(40), false), (None, false)] {
+      let summary = Profiler::new()
+        .exact_row_limit(limit)
+        .profile_frame(&frame)
+        .unwrap();
+      expect_numerical!(summary.columns[0], { quantile_rank_error, .. });
+      expect_categorical!(summary.columns[1], { distinct_error, frequency_error, strings: Some(strings), .. });
+      assert_eq!(quantile_rank_error.is_some(), sketched, "{limit:?}");
+      assert_eq!(distinct_error.is_some(), sketched, "{limit:?}");
+      assert_eq!(frequency_error.is_some(), sketched, "{limit:?}");
+      assert_eq!(strings.total_patterns.is_none(), sketched, "{limit:?}");
+    }
+  }
+
+  #[test]
+  fn rejects_invalid_options_before_reading() {
+    let frame = df!("value" => [1.0, 2.0]).unwrap();
+    let error = |profiler: Profiler| profiler.profile_frame(&frame).unwrap_err().to_string();
+
+    assert!(error(Profiler::new().percentiles([101.0])).contains("Percentile 101"));
+    assert!(error(Profiler::new().histogram(BinStrategy::EqualWidth, 0)).contains("bin"));
+    assert!(error(Profiler::new().heavy_hitters(10, 5)).contains("counters"));
+    assert!(error(Profiler::new().metadata(true)).contains("parquet files"));
+    assert!(error(Profiler::new().filter("value >")).contains("--where"));
+  }
+
+  #[test]
+  fn profiles_a_lazy_query() {
+    let lazy_frame = df!("value" => [1.0, 2.0, 3.0, 4.0])
+      .unwrap()
+      .lazy()
+      .with_column((col("value") * lit(10.0)).alias("scaled"));
+    let summary = Profiler::new()
+      .columns(["scaled"])
+      .filter("value > 2")
+      .profile_lazy(lazy_frame)
+      .unwrap();
+
+    assert_eq!(summary.file, IN_MEMORY);
+    assert_eq!(summary.filter.as_deref(), Some("value > 2"));
+    assert_eq!(summary.n_rows, 2);
+    assert_eq!(summary.columns.len(), 1);
+    expect_numerical!(summary.columns[0], { mean, .. });
+    assert_eq!(*mean, Some(35.0));
+  }
+}
@@ -1,355 +1,433 @@
 use anyhow::{Context, Result};
-use clap::Parser;
-use polars::prelude::*;
+use clap::{ArgGroup, Parser, Subcommand, ValueEnum};
+use polars::prelude::QuantileMethod;
+use serde::Serialize;
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
-/// A CLI tool to summarize Parquet files with shape and statistical information
+use parquet_summarizer::baseline::{self, Tolerances};
+use parquet_summarizer::histogram::BinStrategy;
+use parquet_summarizer::preview::PreviewRows;
+use parquet_summarizer::sampling::Sample;
+use parquet_summarizer::{
+  DEFAULT_DISTINCT_ERROR, DEFAULT_EXACT_ROW_LIMIT, DEFAULT_HEAVY_HITTER_COUNTERS,
+  DEFAULT_SKETCH_SIZE, DEFAULT_TOP_VALUES, Profiler, diff, report, rules,
+};
+
 #[derive(Parser)]
 #[command(name = "parquet-summarizer")]
 #[command(about = "Analyze and summarize Parquet files efficiently", long_about = None)]
-#[command(version)]
+#[command(version, subcommand_negates_reqs = true)]
+#[command(group(
+  ArgGroup::new("sample")
+    .args(["sample_rows", "sample_fraction", "sample_row_groups"])
+    .conflicts_with_all(["metadata_only", "save_baseline", "check_baseline", "rules"])
+))]
+#[command(group(
+  ArgGroup::new("preview")
+    .args(["head", "tail", "random_rows"])
+    .conflicts_with("metadata_only")
+))]
+#[command(group(
+  ArgGroup::new("seeded")
+    .args(["sample_rows", "sample_fraction", "sample_row_groups", "random_rows"])
+    .multiple(true)
+))]
 struct Args {
-  /// Path to the parquet file to analyze
-  input_file: PathBuf,
+  #[command(subcommand)]
+  command: Option<Command>,
+
+  /// Parquet file, directory of parquet files, or glob pattern to analyze.
+  /// Directories and globs are read as one dataset, with Hive partition
+  /// directories (e.g. `year=2024/month=01`) surfaced as columns
+  #[arg(required = true)]
+  input: Option<PathBuf>,
 
   /// Optional output file path. If not provided, prints to stdout
-  #[arg(short, long)]
+  #[arg(short, long, global = true)]
   output: Option<PathBuf>,
 
+  /// Columns to analyze, by name or `^...$` regex (comma separated).
+  /// Defaults to every column
+  #[arg(long, value_delimiter = ',', global = true)]
+  columns: Vec<String>,
+
+  /// Columns to skip, by name or `^...$` regex (comma separated)
+  #[arg(long, value_delimiter = ',', global = true)]
+  exclude_columns: Vec<String>,
+
+  /// Only analyze rows matching this SQL predicate, e.g.
+  /// `--where "country = 'KR' AND amount > 0"`. Row groups whose footer
+  /// statistics rule out any match are skipped
+  #[arg(
+    long = "where",
+    value_name = "PREDICATE",
+    conflicts_with = "metadata_only",
+    global = true
+  )]
+  filter: Option<String>,
+
+  /// Summarize every column separately for each value of this column and
+  /// report the groups side by side
+  #[arg(long, conflicts_with = "metadata_only")]
+  group_by: Option<String>,
+
+  /// Maximum number of groups to report, largest first; each group adds a scan for its
+  /// frequency tables, sketches, and histograms
+  #[arg(long, default_value_t = 10, requires = "group_by")]
+  max_groups: usize,
+
   /// Maximum number of distinct values to consider a column categorical (default: 10)
-  #[arg(long, default_value_t = 10)]
+  #[arg(long, default_value_t = 10, global = true)]
   categorical_threshold: usize,
 
-  /// Process file with reduced memory usage (limits parallelism)
+  /// Additional percentiles (0-100) to report for numerical columns,
+  /// e.g. `--percentiles 1,5,50,95,99`
+  #[arg(long, value_delimiter = ',', global = true)]
+  percentiles: Vec<f64>,
+
+  /// Interpolation method used for quartiles, median, and percentiles
+  #[arg(long, value_enum, default_value_t = QuantileInterpolation::Nearest, global = true)]
+  quantile_method: QuantileInterpolation,
+
+  /// Estimate the median, quartiles, and percentiles with a KLL sketch that
+  /// consumes each column batch by batch instead of sorting it in memory
+  #[arg(long, conflicts_with = "quantile_method", global = true)]
+  approx_quantiles: bool,
+
+  /// Size of the quantile sketch; larger sketches have smaller rank error
+  /// (200 gives about 1.3%)
+  #[arg(
+    long,
+    default_value_t = DEFAULT_SKETCH_SIZE,
+    requires = "approx_quantiles",
+    global = true
+  )]
+  sketch_size: usize,
+
+  /// Include a histogram of every numerical column
   #[arg(long)]
+  histogram: bool,
+
+  /// Number of histogram bins (ignored by the Freedman–Diaconis strategy)
+  #[arg(long, default_value_t = 10, requires = "histogram", value_parser = clap::value_parser!(u16).range(1..))]
+  histogram_bins: u16,
+
+  /// How histogram bin edges are chosen
+  #[arg(long, value_enum, default_value_t = BinStrategy::EqualWidth, requires = "histogram")]
+  histogram_strategy: BinStrategy,
+
+  /// Estimate distinct counts of high-cardinality columns with a HyperLogLog
+  /// sketch instead of counting every distinct value exactly
+  #[arg(long, global = true)]
+  approx_distinct: bool,
+
+  /// Relative standard error of distinct count estimates, in percent, with
+  /// `--approx-distinct` or `--heavy-hitters`
+  #[arg(long, default_value_t = DEFAULT_DISTINCT_ERROR, global = true)]
+  distinct_error: f64,
+
+  /// List the most frequent values of high-cardinality columns from a
+  /// bounded Space-Saving summary instead of counting every distinct value.
+  /// Their distinct counts are estimated as with `--approx-distinct`, and
+  /// text shape patterns are not counted
+  #[arg(long, global = true)]
+  heavy_hitters: bool,
+
+  /// Number of most frequent values listed with `--heavy-hitters`
+  #[arg(
+    long,
+    default_value_t = DEFAULT_TOP_VALUES,
+    requires = "heavy_hitters",
+    global = true
+  )]
+  top_values: usize,
+
+  /// Counters kept by `--heavy-hitters`; each count overstates by at most
+  /// the number of values divided by this
+  #[arg(
+    long,
+    default_value_t = DEFAULT_HEAVY_HITTER_COUNTERS,
+    requires = "heavy_hitters",
+    global = true
+  )]
+  heavy_hitter_counters: usize,
+
+  /// Profile inputs with more rows than this as if `--approx-quantiles` and
+  /// `--heavy-hitters` were given, at their default sizes, since exact
+  /// quantiles and frequency tables hold whole columns in memory
+  #[arg(long, value_name = "ROWS", default_value_t = DEFAULT_EXACT_ROW_LIMIT, global = true)]
+  exact_row_limit: usize,
+
+  /// Compute quantiles, distinct counts, and frequency tables exactly
+  /// whatever the row count, unless a sketch is asked for
+  #[arg(long, conflicts_with = "exact_row_limit", global = true)]
+  exact: bool,
+
+  /// Profile this many uniformly sampled rows instead of all of them;
+  /// statistics become estimates with confidence intervals
+  #[arg(long, value_name = "N")]
+  sample_rows: Option<usize>,
+
+  /// Profile each row with this probability (0-1) instead of all of them
+  #[arg(long, value_name = "F")]
+  sample_fraction: Option<f64>,
+
+  /// Profile this many randomly chosen whole row groups, reading nothing
+  /// but their pages
+  #[arg(long, value_name = "K")]
+  sample_row_groups: Option<usize>,
+
+  /// Seed of the sample and of `--random-rows`; the same seed draws the
+  /// same rows
+  #[arg(long, default_value_t = 0, requires = "seeded")]
+  seed: u64,
+
+  /// Show the first N rows next to the column analysis
+  #[arg(long, value_name = "N")]
+  head: Option<usize>,
+
+  /// Show the last N rows next to the column analysis
+  #[arg(long, value_name = "N")]
+  tail: Option<usize>,
+
+  /// Show N randomly chosen rows next to the column analysis
+  #[arg(long, value_name = "N")]
+  random_rows: Option<usize>,
+
+  /// Process file with reduced memory usage (limits parallelism)
+  #[arg(long, global = true)]
   low_memory: bool,
-}
 
-#[derive(Debug)]
-struct ColumnSummary {
-  name: String,
-  data_type: String,
-  summary: ColumnStats,
+  /// Include Parquet footer metadata: writer, row groups, compression,
+  /// encodings, and sizes (read from the footer, not the data pages)
+  #[arg(long)]
+  metadata: bool,
+
+  /// Summarize columns from Parquet footer statistics only (min, max, null
+  /// and distinct counts) without reading any data pages
+  #[arg(long, global = true)]
+  metadata_only: bool,
+
+  /// Save the column summaries as a JSON baseline for later `--check-baseline`
+  #[arg(long, value_name = "FILE")]
+  save_baseline: Option<PathBuf>,
+
+  /// Compare the summary with a saved baseline and exit with an error when
+  /// the schema or statistics deviate beyond the tolerances
+  #[arg(long, value_name = "FILE")]
+  check_baseline: Option<PathBuf>,
+
+  /// Allowed relative change in row count, in percent
+  #[arg(long, default_value_t = 10.0, requires = "check_baseline")]
+  row_count_tolerance: f64,
+
+  /// Allowed change in a column's null rate, in percentage points
+  #[arg(long, default_value_t = 5.0, requires = "check_baseline")]
+  null_rate_tolerance: f64,
+
+  /// Allowed relative change in mean, standard deviation, quartiles, and
+  /// distinct counts, in percent
+  #[arg(long, default_value_t = 10.0, requires = "check_baseline")]
+  stat_tolerance: f64,
+
+  /// Do not fail the baseline check on columns missing from the baseline
+  #[arg(long, requires = "check_baseline")]
+  allow_new_columns: bool,
+
+  /// Check the data against the data-quality rules in a TOML file and exit
+  /// with an error when any of them fails
+  #[arg(long, value_name = "FILE", conflicts_with = "metadata_only")]
+  rules: Option<PathBuf>,
+
+  /// Output format: human-readable text or machine-readable JSON
+  #[arg(long, value_enum, default_value_t = OutputFormat::Text, global = true)]
+  format: OutputFormat,
 }
 
-#[derive(Debug)]
-enum ColumnStats {
-  Numerical {
-    mean: Option<f64>,
-    std_dev: Option<f64>,
-    q25: Option<f64>,
-    q75: Option<f64>,
-    iqr: Option<f64>,
-  },
-  Categorical {
-    frequency_table: Vec<(String, u32)>,
-    total_unique: usize,
-    showing_top_n: bool,
+#[derive(Subcommand)]
+enum Command {
+  /// Compare two inputs and report schema changes and distribution drift
+  Diff {
+    /// The reference input, e.g. yesterday's export
+    baseline: PathBuf,
+    /// The input to check against the baseline
+    current: PathBuf,
   },
 }
 
-fn main() -> Result<()> {
-  let args = Args::parse();
-
-  // Validate input file exists
-  if !args.input_file.exists() {
-    anyhow::bail!("Input file '{}' does not exist", args.input_file.display());
-  }
-
-  // Analyze the parquet file
-  let summary = analyze_parquet(&args)?;
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+  Text,
+  Json,
+}
 
-  // Generate output
-  let output_text = format_summary(&summary);
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum QuantileInterpolation {
+  Nearest,
+  Lower,
+  Higher,
+  Midpoint,
+  Linear,
+}
 
-  // Write to file or stdout
-  match args.output {
-    Some(output_path) => {
-      let mut file = File::create(&output_path)
-        .with_context(|| format!("Failed to create output file '{}'", output_path.display()))?;
-      file
-        .write_all(output_text.as_bytes())
-        .with_context(|| format!("Failed to write to output file '{}'", output_path.display()))?;
-      println!("Summary written to: {}", output_path.display());
+impl Args {
+  /// The profiling options given on the command line.
+  fn profiler(&self) -> Profiler {
+    let mut profiler = Profiler::new()
+      .columns(&self.columns)
+      .exclude_columns(&self.exclude_columns)
+      .categorical_threshold(self.categorical_threshold)
+      .percentiles(self.percentiles.iter().copied())
+      .quantile_method(self.quantile_method.into())
+      .exact_row_limit((!self.exact).then_some(self.exact_row_limit))
+      .low_memory(self.low_memory)
+      .metadata(self.metadata)
+      .metadata_only(self.metadata_only);
+    if let Some(filter) = &self.filter {
+      profiler = profiler.filter(filter);
     }
-    None => {
-      print!("{output_text}");
+    if let Some(key) = &self.group_by {
+      profiler = profiler.group_by(key, self.max_groups);
+    }
+    if self.approx_quantiles {
+      profiler = profiler.approx_quantiles(self.sketch_size);
+    }
+    if self.approx_distinct || self.heavy_hitters {
+      profiler = profiler.approx_distinct(self.distinct_error);
+    }
+    if self.heavy_hitters {
+      profiler = profiler.heavy_hitters(self.top_values, self.heavy_hitter_counters);
+    }
+    let sample = match (
+      self.sample_rows,
+      self.sample_fraction,
+      self.sample_row_groups,
+    ) {
+      (Some(rows), _, _) => Some(Sample::Rows(rows)),
+      (_, Some(fraction), _) => Some(Sample::Fraction(fraction)),
+      (_, _, Some(row_groups)) => Some(Sample::RowGroups(row_groups)),
+      _ => None,
+    };
+    if let Some(sample) = sample {
+      profiler = profiler.sample(sample, self.seed);
+    }
+    let preview = match (self.head, self.tail, self.random_rows) {
+      (Some(count), _, _) => Some((PreviewRows::Head, count)),
+      (_, Some(count), _) => Some((PreviewRows::Tail, count)),
+      (_, _, Some(count)) => Some((PreviewRows::Random { seed: self.seed }, count)),
+      _ => None,
+    };
+    if let Some((rows, count)) = preview {
+      profiler = profiler.preview(rows, count);
     }
+    if self.histogram {
+      profiler = profiler.histogram(self.histogram_strategy, usize::from(self.histogram_bins));
+    }
+    profiler
   }
-
-  Ok(())
 }
 
-fn analyze_parquet(args: &Args) -> Result<Vec<ColumnSummary>> {
-  // Use lazy loading for efficiency with large files
-  let mut scan_args = ScanArgsParquet::default();
-  if args.low_memory {
-    scan_args.low_memory = true;
-  }
-
-  let lazy_frame = LazyFrame::scan_parquet(&args.input_file, scan_args).with_context(|| {
-    format!(
-      "Failed to scan parquet file '{}'",
-      args.input_file.display()
-    )
-  })?;
-
-  // Collect the dataframe
-  let df = lazy_frame
-    .collect()
-    .with_context(|| "Failed to load parquet data")?;
-
-  let mut summaries = Vec::new();
-
-  // Print shape information
-  println!("📊 Parquet File Analysis");
-  println!("━━━━━━━━━━━━━━━━━━━━━━━━━");
-  println!("📁 File: {}", args.input_file.display());
-  println!("📏 Shape: {} rows × {} columns", df.height(), df.width());
-  println!();
-
-  // Analyze each column
-  for column_name in df.get_column_names() {
-    let column = df
-      .column(column_name)
-      .with_context(|| format!("Failed to get column '{column_name}'"))?;
-
-    // Convert Column to Series
-    let series = column.as_materialized_series().clone();
-
-    let data_type = series.dtype();
-    let summary = analyze_column(&series, args.categorical_threshold)?;
-
-    summaries.push(ColumnSummary {
-      name: column_name.to_string(),
-      data_type: format!("{data_type:?}"),
-      summary,
-    });
+impl From<QuantileInterpolation> for QuantileMethod {
+  fn from(method: QuantileInterpolation) -> Self {
+    match method {
+      QuantileInterpolation::Nearest => QuantileMethod::Nearest,
+      QuantileInterpolation::Lower => QuantileMethod::Lower,
+      QuantileInterpolation::Higher => QuantileMethod::Higher,
+      QuantileInterpolation::Midpoint => QuantileMethod::Midpoint,
+      QuantileInterpolation::Linear => QuantileMethod::Linear,
+    }
   }
-
-  Ok(summaries)
 }
 
-fn analyze_column(column: &Series, categorical_threshold: usize) -> Result<ColumnStats> {
-  let data_type = column.dtype();
-
-  match data_type {
-    // Numerical types
-    DataType::UInt8
-    | DataType::UInt16
-    | DataType::UInt32
-    | DataType::UInt64
-    | DataType::Int8
-    | DataType::Int16
-    | DataType::Int32
-    | DataType::Int64
-    | DataType::Int128
-    | DataType::Float32
-    | DataType::Float64 => analyze_numerical_column(column),
-
-    // String and categorical types
-    DataType::String | DataType::Categorical(_, _) | DataType::Enum(_, _) => {
-      analyze_categorical_column(column, categorical_threshold)
-    }
-
-    // For other types, treat as categorical if they have reasonable number of unique values
-    _ => {
-      let unique_count = column
-        .n_unique()
-        .map_err(|e| anyhow::anyhow!("Failed to count unique values: {}", e))?;
-
-      if unique_count <= categorical_threshold {
-        analyze_categorical_column(column, categorical_threshold)
-      } else {
-        // For complex types with too many unique values, just show basic info
-        Ok(ColumnStats::Categorical {
-          frequency_table: vec![],
-          total_unique: unique_count,
-          showing_top_n: false,
-        })
+fn main() -> Result<()> {
+  let args = Args::parse();
+  let profiler = args.profiler();
+
+  // Failed checks of `--check-baseline` and `--rules`, reported after the
+  // output is written
+  let mut failures = Vec::new();
+
+  let output_text = match &args.command {
+    Some(Command::Diff { baseline, current }) => {
+      let report = diff::diff_parquet(baseline, current, &profiler)?;
+      match args.format {
+        OutputFormat::Text => diff::format_diff(&report),
+        OutputFormat::Json => format_json(&report)?,
       }
     }
-  }
-}
+    None => {
+      let input = args.input.as_deref().with_context(|| "No input given")?;
 
-fn analyze_numerical_column(column: &Series) -> Result<ColumnStats> {
-  // Get statistical measures
-  let mean = column.mean();
-  let std_dev = column.std(1);
-
-  // Calculate quartiles for IQR using quantile_reduce
-  let q25 = column
-    .quantile_reduce(0.25, QuantileMethod::Nearest)
-    .ok()
-    .and_then(|scalar| scalar.value().extract::<f64>());
-
-  let q75 = column
-    .quantile_reduce(0.75, QuantileMethod::Nearest)
-    .ok()
-    .and_then(|scalar| scalar.value().extract::<f64>());
-
-  let iqr = match (q25, q75) {
-    (Some(q25_val), Some(q75_val)) => Some(q75_val - q25_val),
-    _ => None,
-  };
+      // Analyze the parquet file
+      let mut summary = profiler.profile_path(input)?;
 
-  Ok(ColumnStats::Numerical {
-    mean,
-    std_dev,
-    q25,
-    q75,
-    iqr,
-  })
-}
-
-fn analyze_categorical_column(
-  column: &Series,
-  categorical_threshold: usize,
-) -> Result<ColumnStats> {
-  let unique_count = column
-    .n_unique()
-    .map_err(|e| anyhow::anyhow!("Failed to count unique values: {}", e))?;
-
-  // Get value counts
-  let value_counts_result = column.value_counts(false, true, "count".into(), false);
-
-  match value_counts_result {
-    Ok(counts_df) => {
-      let mut frequency_table = Vec::new();
-      let showing_top_n = unique_count > categorical_threshold;
-
-      // Extract the values and counts
-      let values_column = counts_df
-        .column(column.name())
-        .map_err(|e| anyhow::anyhow!("Failed to get values column: {}", e))?;
-      let counts_column = counts_df
-        .column("count")
-        .map_err(|e| anyhow::anyhow!("Failed to get counts column: {}", e))?;
-
-      let limit = if showing_top_n {
-        std::cmp::min(10, unique_count)
-      } else {
-        unique_count
-      };
-
-      for i in 0..std::cmp::min(limit, counts_df.height()) {
-        let value = values_column
-          .get(i)
-          .map_err(|e| anyhow::anyhow!("Failed to get value at index {}: {}", i, e))?;
-        let count = counts_column
-          .get(i)
-          .map_err(|e| anyhow::anyhow!("Failed to get count at index {}: {}", i, e))?;
-
-        let value_str = match value {
-          AnyValue::String(s) => s.to_string(),
-          AnyValue::Categorical(idx, rev_map, _) => rev_map.get(idx).to_string(),
-          _ => format!("{value}"),
+      if let Some(path) = &args.save_baseline {
+        baseline::save_baseline(&summary, path)?;
+      }
+      if let Some(path) = &args.check_baseline {
+        let tolerances = Tolerances {
+          row_count: args.row_count_tolerance,
+          null_rate: args.null_rate_tolerance,
+          statistic: args.stat_tolerance,
+          allow_new_columns: args.allow_new_columns,
         };
-
-        if let AnyValue::UInt32(count_val) = count {
-          frequency_table.push((value_str, count_val));
+        let check = baseline::check_baseline(&summary, path, &tolerances)?;
+        if !check.passed {
+          failures.push(format!(
+            "Baseline check failed with {} violation(s)",
+            check.violations.len()
+          ));
         }
+        summary.baseline_check = Some(check);
       }
-
-      Ok(ColumnStats::Categorical {
-        frequency_table,
-        total_unique: unique_count,
-        showing_top_n,
-      })
-    }
-    Err(_) => {
-      // Fallback: just return unique count
-      Ok(ColumnStats::Categorical {
-        frequency_table: vec![],
-        total_unique: unique_count,
-        showing_top_n: false,
-      })
-    }
-  }
-}
-
-fn format_summary(summaries: &[ColumnSummary]) -> String {
-  let mut output = String::new();
-
-  output.push_str("📋 Column Analysis\n");
-  output.push_str("━━━━━━━━━━━━━━━━━━\n\n");
-
-  for (i, summary) in summaries.iter().enumerate() {
-    output.push_str(&format!(
-      "{}. Column: '{}' ({})\n",
-      i + 1,
-      summary.name,
-      summary.data_type
-    ));
-
-    match &summary.summary {
-      ColumnStats::Numerical {
-        mean,
-        std_dev,
-        q25,
-        q75,
-        iqr,
-      } => {
-        output.push_str("   📈 Numerical Statistics:\n");
-
-        if let Some(mean_val) = mean {
-          output.push_str(&format!("      Mean: {mean_val:.6}\n"));
-        } else {
-          output.push_str("      Mean: N/A (no valid values)\n");
-        }
-
-        if let Some(std_val) = std_dev {
-          output.push_str(&format!("      Std Dev: {std_val:.6}\n"));
-        } else {
-          output.push_str("      Std Dev: N/A (no valid values)\n");
-        }
-
-        match (q25, q75, iqr) {
-          (Some(q25_val), Some(q75_val), Some(iqr_val)) => {
-            output.push_str(&format!("      Q1 (25%): {q25_val:.6}\n"));
-            output.push_str(&format!("      Q3 (75%): {q75_val:.6}\n"));
-            output.push_str(&format!("      IQR: {iqr_val:.6}\n"));
-          }
-          _ => {
-            output.push_str("      Quartiles: N/A (no valid values)\n");
-          }
+      if let Some(path) = &args.rules {
+        let report = rules::check_rules(&profiler.scan(input)?, path)?;
+        let failed = report
+          .results
+          .iter()
+          .filter(|result| !result.passed)
+          .count();
+        if failed > 0 {
+          failures.push(format!("{failed} data-quality rule(s) failed"));
         }
+        summary.rules = Some(report);
       }
 
-      ColumnStats::Categorical {
-        frequency_table,
-        total_unique,
-        showing_top_n,
-      } => {
-        if frequency_table.is_empty() {
-          output.push_str(&format!(
-            "   📊 Categorical: {total_unique} unique values (too many to display)\n"
-          ));
-        } else {
-          if *showing_top_n {
-            output.push_str(&format!(
-              "   📊 Categorical: {total_unique} total unique values (showing top 10):\n"
-            ));
-          } else {
-            output.push_str(&format!(
-              "   📊 Categorical: {total_unique} unique values:\n"
-            ));
-          }
-          for (value, count) in frequency_table {
-            let percentage =
-              (*count as f64 / frequency_table.iter().map(|(_, c)| *c as f64).sum::<f64>()) * 100.0;
-            output.push_str(&format!("      '{value}': {count} ({percentage:.1}%)\n"));
-          }
-        }
+      // Generate output
+      match args.format {
+        OutputFormat::Text => report::format_summary(&summary),
+        OutputFormat::Json => format_json(&summary)?,
       }
     }
+  };
+
+  // Write to file or stdout
+  match args.output {
+    Some(output_path) => {
+      let mut file = File::create(&output_path)
+        .with_context(|| format!("Failed to create output file '{}'", output_path.display()))?;
+      file
+        .write_all(output_text.as_bytes())
+        .with_context(|| format!("Failed to write to output file '{}'", output_path.display()))?;
+      println!("Summary written to: {}", output_path.display());
+    }
+    None => {
+      print!("{output_text}");
+    }
+  }
 
-    output.push('\n');
+  if !failures.is_empty() {
+    anyhow::bail!(failures.join("; "));
   }
 
-  output.push_str("✅ Analysis complete!\n");
+  Ok(())
+}
 
-  output
+fn format_json(summary: &impl Serialize) -> Result<String> {
+  let mut json =
+    serde_json::to_string_pretty(summary).with_context(|| "Failed to serialize summary as JSON")?;
+  json.push('\n');
+  Ok(json)
 }
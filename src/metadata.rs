@@ -0,0 +1,606 @@
+//! Parquet footer metadata: writer, row groups, and per-column storage details.
+
+use anyhow::{Context, Result};
+use polars::io::parquet::metadata::{ParquetStatistics, deserialize};
+use polars::io::parquet::read::{FileMetadata, infer_schema};
+use polars::prelude::*;
+use serde::Serialize;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+
+use crate::{ColumnStats, ColumnSummary, percentage};
+
+#[derive(Debug, Serialize)]
+pub struct FooterMetadata {
+  pub path: String,
+  pub created_by: Option<String>,
+  pub format_version: i32,
+  pub n_rows: usize,
+  pub compressed_size: u64,
+  pub uncompressed_size: u64,
+  pub compression_ratio: Option<f64>,
+  pub row_groups: Vec<RowGroupMetadata>,
+  pub columns: Vec<ColumnChunkMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RowGroupMetadata {
+  pub n_rows: usize,
+  pub compressed_size: u64,
+  pub uncompressed_size: u64,
+}
+
+/// Storage details of one leaf column, aggregated over all row groups.
+#[derive(Debug, Serialize)]
+pub struct ColumnChunkMetadata {
+  pub path: String,
+  pub physical_type: String,
+  pub compression: Vec<String>,
+  pub encodings: Vec<String>,
+  pub compressed_size: u64,
+  pub uncompressed_size: u64,
+  pub compression_ratio: Option<f64>,
+}
+
+/// Reads the footer of a parquet file. Only the footer bytes are read, never
+/// the data pages, so this is cheap even for very large files.
+pub(crate) fn read_file_metadata(path: &Path) -> Result<Arc<FileMetadata>> {
+  let file = File::open(path).with_context(|| format!("Failed to open '{}'", path.display()))?;
+  let metadata = ParquetReader::new(file)
+    .get_metadata()
+    .with_context(|| format!("Failed to read parquet footer of '{}'", path.display()))?
+    .clone();
+  Ok(metadata)
+}
+
+pub fn read_footer(path: &Path) -> Result<FooterMetadata> {
+  let metadata = read_file_metadata(path)?;
+
+  let row_groups = metadata
+    .row_groups
+    .iter()
+    .map(|row_group| RowGroupMetadata {
+      n_rows: row_group.num_rows(),
+      compressed_size: row_group.compressed_size() as u64,
+      uncompressed_size: row_group.total_byte_size() as u64,
+    })
+    .collect::<Vec<_>>();
+
+  let mut columns: Vec<ColumnChunkMetadata> = Vec::new();
+  for row_group in &metadata.row_groups {
+    for (index, chunk) in row_group.parquet_columns().iter().enumerate() {
+      if columns.len() <= index {
+        columns.push(ColumnChunkMetadata {
+          path: chunk.descriptor().path_in_schema.join("."),
+          physical_type: format!("{:?}", chunk.physical_type()),
+          compression: vec![],
+          encodings: vec![],
+          compressed_size: 0,
+          uncompressed_size: 0,
+          compression_ratio: None,
+        });
+      }
+
+      let column = &mut columns[index];
+      let codec = format!("{:?}", chunk.compression());
+      if !column.compression.contains(&codec) {
+        column.compression.push(codec);
+      }
+      for encoding in chunk.column_encoding() {
+        let name = encoding_name(encoding.0);
+        if !column.encodings.contains(&name) {
+          column.encodings.push(name);
+        }
+      }
+      column.compressed_size += chunk.compressed_size().max(0) as u64;
+      column.uncompressed_size += chunk.uncompressed_size().max(0) as u64;
+    }
+  }
+  for column in &mut columns {
+    column.compression_ratio = compression_ratio(column.uncompressed_size, column.compressed_size);
+  }
+
+  let compressed_size = columns.iter().map(|column| column.compressed_size).sum();
+  let uncompressed_size = columns.iter().map(|column| column.uncompressed_size).sum();
+
+  Ok(FooterMetadata {
+    path: path.display().to_string(),
+    created_by: metadata.created_by.clone(),
+    format_version: metadata.version,
+    n_rows: metadata.num_rows,
+    compressed_size,
+    uncompressed_size,
+    compression_ratio: compression_ratio(uncompressed_size, compressed_size),
+    row_groups,
+    columns,
+  })
+}
+
+/// Column chunk statistics of one column, merged across row groups and files.
+#[derive(Default)]
+struct StatisticsAccumulator {
+  data_type: Option<DataType>,
+  mins: Vec<Series>,
+  maxs: Vec<Series>,
+  null_count: Option<u64>,
+  distinct_count: Option<u64>,
+  n_rows: usize,
+  n_chunks: usize,
+  missing_null_count: bool,
+  nested: bool,
+}
+
+impl StatisticsAccumulator {
+  fn add(&mut self, statistics: Option<ParquetStatistics>, n_rows: usize) -> Result<()> {
+    self.n_chunks += 1;
+    self.n_rows += n_rows;
+
+    // Dictionary-encoded columns carry the statistics of their values
+    let statistics = match statistics {
+      Some(ParquetStatistics::Dictionary(_, values, _)) => values.map(|values| *values),
+      other => other,
+    };
+
+    let column = match statistics {
+      Some(ParquetStatistics::Column(column)) => column,
+      // Lists hold the statistics of their elements, not of their rows
+      Some(ParquetStatistics::List(_) | ParquetStatistics::FixedSizeList(_, _)) => {
+        self.nested = true;
+        self.missing_null_count = true;
+        return Ok(());
+      }
+      // Chunks written without statistics
+      _ => {
+        self.missing_null_count = true;
+        return Ok(());
+      }
+    };
+
+    let column = column.into_arrow()?;
+    match column.null_count {
+      Some(null_count) => *self.null_count.get_or_insert(0) += null_count,
+      None => self.missing_null_count = true,
+    }
+    self.distinct_count = column.distinct_count;
+    if let Some(min) = column.min_value {
+      self.mins.push(self.to_series(min)?);
+    }
+    if let Some(max) = column.max_value {
+      self.maxs.push(self.to_series(max)?);
+    }
+    Ok(())
+  }
+
+  /// Statistics may come back in their physical representation (e.g. raw
+  /// epoch integers for timestamps), so restore the column's logical type.
+  fn to_series(&self, array: ArrayRef) -> Result<Series> {
+    let series = Series::from_arrow(PlSmallStr::EMPTY, array)?;
+    Ok(match &self.data_type {
+      Some(data_type) if series.dtype() != data_type => series.cast(data_type)?,
+      _ => series,
+    })
+  }
+
+  fn finish(self, name: &str) -> Result<ColumnSummary> {
+    let min = reduce_extreme(self.mins, |series| series.min_reduce())?;
+    let max = reduce_extreme(self.maxs, |series| series.max_reduce())?;
+
+    let null_count = if self.missing_null_count {
+      None
+    } else {
+      self.null_count
+    };
+
+    Ok(ColumnSummary {
+      name: name.to_string(),
+      data_type: format!("{:?}", self.data_type.unwrap_or(DataType::Null)),
+      null_count,
+      null_percentage: null_count.and_then(|null_count| percentage(null_count, self.n_rows)),
+      null_percentage_interval: None,
+      summary: ColumnStats::Footer {
+        min,
+        max,
+        // A distinct count cannot be combined across chunks, so it is only
+        // meaningful when the whole column lives in a single chunk
+        distinct_count: if self.n_chunks == 1 {
+          self.distinct_count
+        } else {
+          None
+        },
+        nested: self.nested,
+      },
+    })
+  }
+}
+
+fn reduce_extreme(
+  values: Vec<Series>,
+  reduce: impl Fn(&Series) -> PolarsResult<Scalar>,
+) -> Result<Option<String>> {
+  let mut values = values.into_iter();
+  let Some(mut combined) = values.next() else {
+    return Ok(None);
+  };
+  for series in values {
+    combined.append(&series)?;
+  }
+
+  let scalar = reduce(&combined)?;
+  let value = scalar.as_any_value();
+  Ok(match value {
+    AnyValue::Null => None,
+    _ => Some(match value.get_str() {
+      Some(s) => s.to_string(),
+      None => format!("{value}"),
+    }),
+  })
+}
+
+/// Builds column summaries purely from the min, max, null count, and distinct
+/// count recorded in the column chunk statistics of every row group, without
+/// reading any data pages. Struct columns are summarized per field, named by
+/// their dotted path (`payload.geo.lat`), as that is where statistics are
+/// recorded.
+pub(crate) fn summarize_from_footers(paths: &[PathBuf]) -> Result<Vec<ColumnSummary>> {
+  let mut names: Vec<String> = Vec::new();
+  let mut accumulators: PlHashMap<String, StatisticsAccumulator> = PlHashMap::new();
+
+  for path in paths {
+    let metadata = read_file_metadata(path)?;
+    let schema = infer_schema(&metadata)
+      .with_context(|| format!("Failed to infer schema of '{}'", path.display()))?;
+
+    for field in schema.iter_values() {
+      for (leaf, data_type, _) in leaves(field.name.to_string(), field, None) {
+        if !accumulators.contains_key(&leaf) {
+          names.push(leaf.clone());
+          accumulators.insert(
+            leaf,
+            StatisticsAccumulator {
+              data_type: Some(data_type),
+              ..Default::default()
+            },
+          );
+        }
+      }
+    }
+
+    for row_group in &metadata.row_groups {
+      let mut chunks = row_group.parquet_columns().iter();
+      for field in schema.iter_values() {
+        let statistics = deserialize(field, &mut chunks).with_context(|| {
+          format!(
+            "Failed to read statistics of column '{}' in '{}'",
+            field.name,
+            path.display()
+          )
+        })?;
+        for (leaf, _, statistics) in leaves(field.name.to_string(), field, statistics) {
+          if let Some(accumulator) = accumulators.get_mut(&leaf) {
+            accumulator.add(statistics, row_group.num_rows())?;
+          }
+        }
+      }
+    }
+  }
+
+  names
+    .iter()
+    .map(|name| accumulators.remove(name).unwrap_or_default().finish(name))
+    .collect()
+}
+
+/// Builds summaries of the Hive partition columns in `schema` from the
+/// `key=value` directories of the files, since partition values are kept in
+/// paths rather than footers. Every row of a file shares its partition
+/// values, so none are null.
+pub(crate) fn summarize_partitions(
+  paths: &[PathBuf],
+  schema: &Schema,
+  n_rows: usize,
+) -> Result<Vec<ColumnSummary>> {
+  let mut values: PlHashMap<String, Vec<String>> = PlHashMap::new();
+  for path in paths {
+    for component in path.parent().into_iter().flat_map(Path::components) {
+      let component = component.as_os_str().to_string_lossy();
+      if let Some((key, value)) = component.split_once('=')
+        && schema.contains(key)
+      {
+        values
+          .entry(key.to_string())
+          .or_default()
+          .push(value.to_string());
+      }
+    }
+  }
+
+  schema
+    .iter()
+    .filter_map(|(name, data_type)| {
+      let values = values.remove(name.as_str())?;
+      Some(summarize_partition(name, data_type, values, n_rows))
+    })
+    .collect()
+}
+
+fn summarize_partition(
+  name: &str,
+  data_type: &DataType,
+  values: Vec<String>,
+  n_rows: usize,
+) -> Result<ColumnSummary> {
+  let values = Series::new(name.into(), values)
+    .cast(data_type)
+    .with_context(|| format!("Failed to read the values of partition column '{name}'"))?;
+
+  Ok(ColumnSummary {
+    name: name.to_string(),
+    data_type: format!("{data_type:?}"),
+    null_count: Some(0),
+    null_percentage: percentage(0, n_rows),
+    null_percentage_interval: None,
+    summary: ColumnStats::Footer {
+      min: reduce_extreme(vec![values.clone()], |series| series.min_reduce())?,
+      max: reduce_extreme(vec![values.clone()], |series| series.max_reduce())?,
+      distinct_count: Some(values.n_unique()? as u64),
+      nested: false,
+    },
+  })
+}
+
+/// Splits a column into the fields its statistics are recorded for: struct
+/// fields are followed down to the primitive and list fields inside, named
+/// by their dotted path, each with its own statistics.
+fn leaves(
+  name: String,
+  field: &ArrowField,
+  statistics: Option<ParquetStatistics>,
+) -> Vec<(String, DataType, Option<ParquetStatistics>)> {
+  let ArrowDataType::Struct(fields) = field.dtype() else {
+    return vec![(name, DataType::from_arrow_field(field), statistics)];
+  };
+  let children = match statistics {
+    Some(ParquetStatistics::Struct(children)) => children.into_vec(),
+    _ => fields.iter().map(|_| None).collect(),
+  };
+  fields
+    .iter()
+    .zip(children)
+    .flat_map(|(child, statistics)| leaves(format!("{name}.{}", child.name), child, statistics))
+    .collect()
+}
+
+fn compression_ratio(uncompressed_size: u64, compressed_size: u64) -> Option<f64> {
+  (compressed_size > 0).then(|| uncompressed_size as f64 / compressed_size as f64)
+}
+
+/// Names of the encodings defined by the Parquet format specification.
+fn encoding_name(code: i32) -> String {
+  match code {
+    0 => "PLAIN".to_string(),
+    2 => "PLAIN_DICTIONARY".to_string(),
+    3 => "RLE".to_string(),
+    4 => "BIT_PACKED".to_string(),
+    5 => "DELTA_BINARY_PACKED".to_string(),
+    6 => "DELTA_LENGTH_BYTE_ARRAY".to_string(),
+    7 => "DELTA_BYTE_ARRAY".to_string(),
+    8 => "RLE_DICTIONARY".to_string(),
+    9 => "BYTE_STREAM_SPLIT".to_string(),
+    _ => format!("UNKNOWN({code})"),
+  }
+}
+
+pub fn format_bytes(bytes: u64) -> String {
+  const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+  let mut value = bytes as f64;
+  let mut unit = 0;
+  while value >= 1024.0 && unit < UNITS.len() - 1 {
+    value /= 1024.0;
+    unit += 1;
+  }
+
+  if unit == 0 {
+    format!("{bytes} B")
+  } else {
+    format!("{value:.1} {}", UNITS[unit])
+  }
+}
+
+fn format_ratio(ratio: Option<f64>) -> String {
+  match ratio {
+    Some(ratio) => format!("{ratio:.2}x"),
+    None => "N/A".to_string(),
+  }
+}
+
+pub fn format_footer(footer: &FooterMetadata) -> String {
+  let mut output = String::new();
+
+  output.push_str(&format!("🧾 Parquet Metadata: {}\n", footer.path));
+  output.push_str(&format!(
+    "   Created by: {}\n",
+    footer.created_by.as_deref().unwrap_or("unknown")
+  ));
+  output.push_str(&format!("   Format version: {}\n", footer.format_version));
+  output.push_str(&format!(
+    "   Size: {} compressed / {} uncompressed (ratio {})\n",
+    format_bytes(footer.compressed_size),
+    format_bytes(footer.uncompressed_size),
+    format_ratio(footer.compression_ratio)
+  ));
+
+  output.push_str(&format!("   Row groups: {}\n", footer.row_groups.len()));
+  for (i, row_group) in footer.row_groups.iter().enumerate() {
+    output.push_str(&format!(
+      "      #{i}: {} rows, {} compressed / {} uncompressed\n",
+      row_group.n_rows,
+      format_bytes(row_group.compressed_size),
+      format_bytes(row_group.uncompressed_size)
+    ));
+  }
+
+  output.push_str("   Columns:\n");
+  for column in &footer.columns {
+    output.push_str(&format!(
+      "      {} ({}): {}; encodings {}; {} compressed / {} uncompressed (ratio {})\n",
+      column.path,
+      column.physical_type,
+      column.compression.join(", "),
+      column.encodings.join(", "),
+      format_bytes(column.compressed_size),
+      format_bytes(column.uncompressed_size),
+      format_ratio(column.compression_ratio)
+    ));
+  }
+
+  output.push('\n');
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::{TempDir, expect_stats};
+
+  /// Writes a file of two row groups with a struct and a list column.
+  fn write_nested(path: &Path) {
+    let tags = Series::new(
+      "tags".into(),
+      [
+        Series::new("".into(), ["a"]),
+        Series::new("".into(), ["b", "c"]),
+        Series::new("".into(), Vec::<&str>::new()),
+        Series::new("".into(), ["d"]),
+      ],
+    );
+    let mut frame = df!(
+      "id" => [1i64, 2, 3, 4],
+      "user_id" => [Some(7i64), None, Some(3), Some(9)],
+      "lat" => [1.5, -2.0, 0.0, 4.0],
+      "tags" => tags,
+    )
+    .unwrap()
+    .lazy()
+    .select([
+      col("id"),
+      as_struct(vec![
+        col("user_id"),
+        as_struct(vec![col("lat")]).alias("geo"),
+      ])
+      .alias("payload"),
+      col("tags"),
+    ])
+    .collect()
+    .unwrap();
+    ParquetWriter::new(File::create(path).unwrap())
+      .with_row_group_size(Some(2))
+      .finish(&mut frame)
+      .unwrap();
+  }
+
+  fn footer_stats(summary: &ColumnSummary) -> (&Option<String>, &Option<String>, bool) {
+    expect_stats!(
+      summary,
+      Footer {
+        min,
+        max,
+        nested,
+        ..
+      }
+    );
+    (min, max, *nested)
+  }
+
+  #[test]
+  fn summarizes_struct_fields_by_their_dotted_path() {
+    let root = TempDir::new("footers");
+    let path = root.join("nested.parquet");
+    write_nested(&path);
+    let columns = summarize_from_footers(std::slice::from_ref(&path)).unwrap();
+
+    let names = columns
+      .iter()
+      .map(|column| column.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, ["id", "payload.user_id", "payload.geo.lat", "tags"]);
+
+    // Extremes and null counts are merged across both row groups
+    let user_id = &columns[1];
+    assert_eq!(user_id.data_type, "Int64");
+    assert_eq!(user_id.null_count, Some(1));
+    let (min, max, nested) = footer_stats(user_id);
+    assert_eq!(
+      (min.as_deref(), max.as_deref(), nested),
+      (Some("3"), Some("9"), false)
+    );
+    let (min, max, _) = footer_stats(&columns[2]);
+    assert_eq!(
+      (min.as_deref(), max.as_deref()),
+      (Some("-2.0"), Some("4.0"))
+    );
+
+    let (min, max, nested) = footer_stats(&columns[3]);
+    assert_eq!((min, max, nested), (&None, &None, true));
+    assert_eq!(columns[3].null_count, None);
+  }
+
+  #[test]
+  fn reads_row_groups_and_column_chunks_from_the_footer() {
+    let root = TempDir::new("footer");
+    let path = root.join("footer.parquet");
+    let mut frame = df!(
+      "id" => (0..10i64).collect::<Vec<_>>(),
+      "name" => (0..10).map(|i| format!("name-{i}")).collect::<Vec<_>>(),
+    )
+    .unwrap();
+    ParquetWriter::new(File::create(&path).unwrap())
+      .with_compression(ParquetCompression::Snappy)
+      .with_row_group_size(Some(5))
+      .finish(&mut frame)
+      .unwrap();
+    let footer = read_footer(&path).unwrap();
+
+    assert_eq!(footer.n_rows, 10);
+    let row_groups = footer
+      .row_groups
+      .iter()
+      .map(|row_group| row_group.n_rows)
+      .collect::<Vec<_>>();
+    assert_eq!(row_groups, [5, 5]);
+
+    // Chunks of every row group are folded into one entry per column
+    let paths = footer
+      .columns
+      .iter()
+      .map(|column| column.path.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(paths, ["id", "name"]);
+    assert_eq!(footer.columns[0].physical_type, "Int64");
+    assert_eq!(footer.columns[1].physical_type, "ByteArray");
+    for column in &footer.columns {
+      assert_eq!(column.compression, ["Snappy"]);
+      assert!(!column.encodings.is_empty());
+    }
+    assert_eq!(
+      footer.compressed_size,
+      footer
+        .columns
+        .iter()
+        .map(|column| column.compressed_size)
+        .sum::<u64>()
+    );
+  }
+
+  #[test]
+  fn formats_sizes_and_ratios() {
+    assert_eq!(format_bytes(512), "512 B");
+    assert_eq!(format_bytes(1536), "1.5 KiB");
+    assert_eq!(format_bytes(3 * 1024 * 1024 * 1024), "3.0 GiB");
+    assert_eq!(compression_ratio(300, 100), Some(3.0));
+    assert_eq!(compression_ratio(300, 0), None);
+    assert_eq!(format_ratio(Some(2.5)), "2.50x");
+    assert_eq!(encoding_name(8), "RLE_DICTIONARY");
+    assert_eq!(encoding_name(42), "UNKNOWN(42)");
+  }
+}
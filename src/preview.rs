@@ -0,0 +1,411 @@
+//! Example rows shown next to the column analysis: the first rows, the last
+//! rows, or random ones, rendered as a table that fits the terminal.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{collect_streaming, sampling};
+
+/// Longest value printed in a cell before it is cut short.
+const MAX_CELL_WIDTH: usize = 40;
+
+/// Narrowest a cell is cut to when a column does not fit the terminal.
+const MIN_CELL_WIDTH: usize = 8;
+
+/// Table width when the terminal does not report its own.
+const DEFAULT_WIDTH: usize = 120;
+
+/// Indent of every table line, as in the rest of the report.
+const INDENT: &str = "   ";
+
+const SEPARATOR: &str = " │ ";
+
+/// Which rows are shown.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "from", rename_all = "snake_case")]
+pub enum PreviewRows {
+  Head,
+  Tail,
+  /// Drawn uniformly, the same ones for the same seed
+  Random {
+    seed: u64,
+  },
+}
+
+#[derive(Debug, Serialize)]
+pub struct Preview {
+  pub rows: PreviewRows,
+  pub columns: Vec<String>,
+  /// Numerical columns, which are right-aligned
+  #[serde(skip)]
+  numeric: Vec<bool>,
+  /// One entry per row and column, in full, with lists and structs spelled
+  /// out and nulls as `None`
+  pub values: Vec<Vec<Option<String>>>,
+}
+
+/// Collects `count` example rows of the frame.
+pub(crate) fn preview_rows(
+  lazy_frame: &LazyFrame,
+  rows: PreviewRows,
+  count: usize,
+) -> Result<Preview> {
+  let selected = match rows {
+    PreviewRows::Head => lazy_frame.clone().limit(count as IdxSize),
+    PreviewRows::Tail => lazy_frame.clone().tail(count as IdxSize),
+    PreviewRows::Random { seed } => sampling::sample_rows(lazy_frame, count, seed)?.0,
+  };
+  let frame = collect_streaming(selected).with_context(|| "Failed to read preview rows")?;
+
+  let values = (0..frame.height())
+    .map(|row| {
+      frame
+        .get_columns()
+        .iter()
+        .map(|column| match column.get(row)? {
+          AnyValue::Null => Ok(None),
+          value => Ok(Some(render(&value, false))),
+        })
+        .collect::<PolarsResult<Vec<_>>>()
+    })
+    .collect::<PolarsResult<Vec<_>>>()?;
+
+  Ok(Preview {
+    rows,
+    columns: frame
+      .get_column_names()
+      .into_iter()
+      .map(|name| name.to_string())
+      .collect(),
+    numeric: frame
+      .dtypes()
+      .iter()
+      .map(|data_type| data_type.is_primitive_numeric() || data_type.is_decimal())
+      .collect(),
+    values,
+  })
+}
+
+/// Spells out a value, with lists as `[a, b]` and structs as `{field: a}`.
+/// Strings are quoted inside lists and structs, so separators stay visible.
+fn render(value: &AnyValue, nested: bool) -> String {
+  let join = |values: &mut dyn Iterator<Item = String>| values.collect::<Vec<_>>().join(", ");
+  match value {
+    AnyValue::Null => "null".to_string(),
+    AnyValue::String(text) if nested => format!("{text:?}"),
+    AnyValue::StringOwned(text) if nested => format!("{:?}", text.as_str()),
+    AnyValue::String(text) => text.to_string(),
+    AnyValue::StringOwned(text) => text.to_string(),
+    AnyValue::List(values) | AnyValue::Array(values, _) => {
+      format!(
+        "[{}]",
+        join(&mut values.iter().map(|value| render(&value, true)))
+      )
+    }
+    AnyValue::Struct(_, _, fields) => format!(
+      "{{{}}}",
+      join(
+        &mut fields
+          .iter()
+          .zip(value._iter_struct_av())
+          .map(|(field, value)| format!("{}: {}", field.name(), render(&value, true)))
+      )
+    ),
+    AnyValue::StructOwned(payload) => format!(
+      "{{{}}}",
+      join(
+        &mut payload
+          .1
+          .iter()
+          .zip(&payload.0)
+          .map(|(field, value)| format!("{}: {}", field.name(), render(value, true)))
+      )
+    ),
+    value => value.to_string(),
+  }
+}
+
+/// Width available to tables, from the `COLUMNS` variable that shells set
+/// for the terminal.
+fn terminal_width() -> usize {
+  std::env::var("COLUMNS")
+    .ok()
+    .and_then(|columns| columns.parse().ok())
+    .filter(|width| *width > 0)
+    .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// Cuts a cell to `width` characters, escaping line breaks and other
+/// control characters so every row stays on one line.
+fn fit(text: &str, width: usize) -> String {
+  let escaped = text
+    .chars()
+    .flat_map(|c| {
+      if c.is_control() {
+        c.escape_default().collect::<Vec<_>>()
+      } else {
+        vec![c]
+      }
+    })
+    .collect::<String>();
+  match escaped.char_indices().nth(width.saturating_sub(1)) {
+    Some((end, _)) if escaped.chars().count() > width => format!("{}…", &escaped[..end]),
+    _ => escaped,
+  }
+}
+
+/// Renders the rows as a table, numbered from one. Columns that do not fit
+/// next to each other within the terminal width continue in further tables
+/// below, each led by the row numbers.
+pub fn format_preview(preview: &Preview) -> String {
+  format_preview_within(preview, terminal_width())
+}
+
+fn format_preview_within(preview: &Preview, width: usize) -> String {
+  let count = preview.values.len();
+  let title = match preview.rows {
+    PreviewRows::Head => format!("first {count} rows"),
+    PreviewRows::Tail => format!("last {count} rows"),
+    PreviewRows::Random { .. } => format!("{count} random rows"),
+  };
+  let mut output = format!("👀 Preview ({title})\n");
+  output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+  if preview.values.is_empty() {
+    output.push_str("   No rows\n\n");
+    return output;
+  }
+
+  let available = width.saturating_sub(INDENT.len());
+  let numbers = (1..=preview.values.len())
+    .map(|row| row.to_string())
+    .collect::<Vec<_>>();
+  let number_width = numbers.last().map_or(1, |number| number.len()).max(1);
+  let cell_limit = MAX_CELL_WIDTH
+    .min(available.saturating_sub(number_width + SEPARATOR.chars().count()))
+    .max(MIN_CELL_WIDTH);
+
+  // Every column as its cut header and cells, with the width they need
+  let columns = preview
+    .columns
+    .iter()
+    .enumerate()
+    .map(|(index, name)| {
+      let header = fit(name, cell_limit);
+      let cells = preview
+        .values
+        .iter()
+        .map(|row| fit(row[index].as_deref().unwrap_or("null"), cell_limit))
+        .collect::<Vec<_>>();
+      let width = cells
+        .iter()
+        .chain([&header])
+        .map(|cell| cell.chars().count())
+        .max()
+        .unwrap_or(0);
+      (header, cells, width, preview.numeric[index])
+    })
+    .collect::<Vec<_>>();
+
+  // Columns are packed into tables no wider than the terminal
+  let mut tables: Vec<Vec<usize>> = vec![];
+  let mut used = number_width;
+  for (index, (_, _, width, _)) in columns.iter().enumerate() {
+    match tables.last_mut() {
+      Some(table) if used + SEPARATOR.chars().count() + width <= available => table.push(index),
+      _ => {
+        tables.push(vec![index]);
+        used = number_width;
+      }
+    }
+    used += SEPARATOR.chars().count() + width;
+  }
+
+  for table in tables {
+    let line = |first: String, cells: Vec<String>| {
+      let mut line = first;
+      for cell in cells {
+        line.push_str(SEPARATOR);
+        line.push_str(&cell);
+      }
+      format!("{INDENT}{}\n", line.trim_end())
+    };
+
+    output.push_str(&line(
+      format!("{:>number_width$}", "#"),
+      table
+        .iter()
+        .map(|index| {
+          let (header, _, width, numeric) = &columns[*index];
+          if *numeric {
+            format!("{header:>width$}")
+          } else {
+            format!("{header:<width$}")
+          }
+        })
+        .collect(),
+    ));
+    let rule = table
+      .iter()
+      .map(|index| "─".repeat(columns[*index].2))
+      .collect::<Vec<_>>()
+      .join("─┼─");
+    output.push_str(&format!("{INDENT}{}─┼─{rule}\n", "─".repeat(number_width)));
+    for (row, number) in numbers.iter().enumerate() {
+      output.push_str(&line(
+        format!("{number:>number_width$}"),
+        table
+          .iter()
+          .map(|index| {
+            let (_, cells, width, numeric) = &columns[*index];
+            if *numeric {
+              format!("{:>width$}", cells[row])
+            } else {
+              format!("{:<width$}", cells[row])
+            }
+          })
+          .collect(),
+      ));
+    }
+    output.push('\n');
+  }
+
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn frame() -> LazyFrame {
+    let tags = Series::new(
+      "tags".into(),
+      [
+        Series::new("".into(), ["a", "b, c"]),
+        Series::new("".into(), Vec::<&str>::new()),
+        Series::new("".into(), ["d"]),
+        Series::new("".into(), ["e"]),
+      ],
+    );
+    df!(
+      "id" => [1i64, 2, 3, 4],
+      "name" => [Some("Ann"), None, Some("line\nbreak"), Some("Dee")],
+      "tags" => tags,
+    )
+    .unwrap()
+    .lazy()
+    .with_column(as_struct(vec![col("id"), col("name")]).alias("owner"))
+  }
+
+  #[test]
+  fn spells_out_lists_and_structs() {
+    let preview = preview_rows(&frame(), PreviewRows::Head, 2).unwrap();
+
+    assert_eq!(preview.columns, ["id", "name", "tags", "owner"]);
+    assert_eq!(preview.numeric, [true, false, false, false]);
+    let row = |values: [Option<&str>; 4]| values.map(|value| value.map(str::to_string));
+    assert_eq!(
+      preview.values,
+      [
+        row([
+          Some("1"),
+          Some("Ann"),
+          Some("[\"a\", \"b, c\"]"),
+          Some("{id: 1, name: \"Ann\"}")
+        ]),
+        row([Some("2"), None, Some("[]"), Some("{id: 2, name: null}")]),
+      ]
+    );
+  }
+
+  #[test]
+  fn takes_the_last_or_random_rows() {
+    let ids = |preview: &Preview| {
+      preview
+        .values
+        .iter()
+        .map(|row| row[0].clone().unwrap())
+        .collect::<Vec<_>>()
+    };
+
+    let tail = preview_rows(&frame(), PreviewRows::Tail, 2).unwrap();
+    assert_eq!(ids(&tail), ["3", "4"]);
+
+    let random = preview_rows(&frame(), PreviewRows::Random { seed: 7 }, 3).unwrap();
+    let again = preview_rows(&frame(), PreviewRows::Random { seed: 7 }, 3).unwrap();
+    assert_eq!(random.values.len(), 3);
+    assert_eq!(ids(&random), ids(&again));
+
+    let all = preview_rows(&frame(), PreviewRows::Head, 10).unwrap();
+    assert_eq!(ids(&all), ["1", "2", "3", "4"]);
+  }
+
+  #[test]
+  fn escapes_and_cuts_cells() {
+    assert_eq!(fit("short", 10), "short");
+    assert_eq!(fit("exactly 10", 10), "exactly 10");
+    assert_eq!(fit("one too long", 11), "one too lo…");
+    assert_eq!(fit("a\tb\nc", 10), "a\\tb\\nc");
+    assert_eq!(fit("ééééé", 3), "éé…");
+  }
+
+  #[test]
+  fn aligns_numbers_right_and_text_left() {
+    let preview = preview_rows(
+      &frame().select([col("id"), col("name")]),
+      PreviewRows::Head,
+      4,
+    )
+    .unwrap();
+    let output = format_preview_within(&preview, 120);
+
+    assert_eq!(
+      output,
+      concat!(
+        "👀 Preview (first 4 rows)\n",
+        "━━━━━━━━━━━━━━━━━━━━━━━━━\n",
+        "   # │ id │ name\n",
+        "   ──┼────┼────────────\n",
+        "   1 │  1 │ Ann\n",
+        "   2 │  2 │ null\n",
+        "   3 │  3 │ line\\nbreak\n",
+        "   4 │  4 │ Dee\n",
+        "\n",
+      )
+    );
+  }
+
+  #[test]
+  fn continues_wide_rows_in_further_tables() {
+    let preview = preview_rows(&frame(), PreviewRows::Head, 1).unwrap();
+    let output = format_preview_within(&preview, 30);
+
+    // Every table is led by the row numbers and stays within the width
+    let headers = output
+      .lines()
+      .filter(|line| line.starts_with("   # │"))
+      .collect::<Vec<_>>();
+    assert_eq!(headers, ["   # │ id │ name", "   # │ tags", "   # │ owner"]);
+    assert!(
+      output.lines().all(|line| line.chars().count() <= 30),
+      "{output}"
+    );
+    assert!(
+      output.contains("   1 │ {id: 1, name: \"Ann\"}\n"),
+      "{output}"
+    );
+  }
+
+  #[test]
+  fn titles_the_preview_by_its_rows() {
+    let empty = frame().filter(col("id").gt(lit(10)));
+    let preview = preview_rows(&empty, PreviewRows::Random { seed: 1 }, 5).unwrap();
+    assert_eq!(
+      format_preview_within(&preview, 120),
+      "👀 Preview (0 random rows)\n━━━━━━━━━━━━━━━━━━━━━━━━━\n   No rows\n\n"
+    );
+
+    let preview = preview_rows(&frame(), PreviewRows::Tail, 2).unwrap();
+    assert!(format_preview_within(&preview, 120).starts_with("👀 Preview (last 2 rows)\n"));
+  }
+}
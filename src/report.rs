@@ -0,0 +1,398 @@
+//! The human-readable report printed by the CLI.
+
+use crate::sampling::{self, ConfidenceInterval};
+use crate::{
+  ColumnStats, ColumnSummary, ParquetSummary, Percentile, baseline, groups, histogram, metadata,
+  preview, rules, strings,
+};
+
+/// Renders a summary as the human-readable report printed by the CLI.
+pub fn format_summary(summary: &ParquetSummary) -> String {
+  let mut output = String::new();
+
+  // Shape information
+  output.push_str("📊 Parquet File Analysis\n");
+  output.push_str("━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+  output.push_str(&format!("📁 File: {}\n", summary.file));
+  output.push_str(&format!(
+    "📏 Shape: {} rows × {} columns\n",
+    summary.n_rows, summary.n_columns
+  ));
+  if let Some(filter) = &summary.filter {
+    output.push_str(&format!("🔎 Filter: {filter}\n"));
+  }
+  if let Some(sample) = &summary.sample {
+    output.push_str(&format!(
+      "🎲 Sample: {} ({:.2}% of rows, seed {}); statistics are estimates with {:.0}% confidence intervals\n",
+      sample.sample,
+      sample.fraction * 100.0,
+      sample.seed,
+      sample.confidence_level * 100.0
+    ));
+  }
+  if summary.files.len() > 1 {
+    output.push_str(&format!("🗂️ Files: {}\n", summary.files.len()));
+    for file in &summary.files {
+      output.push_str(&format!("   {}: {} rows\n", file.path, file.n_rows));
+    }
+  }
+  output.push('\n');
+
+  if let Some(footers) = &summary.metadata {
+    for footer in footers {
+      output.push_str(&metadata::format_footer(footer));
+    }
+  }
+
+  if let Some(check) = &summary.baseline_check {
+    output.push_str(&baseline::format_check(check));
+  }
+
+  if let Some(report) = &summary.rules {
+    output.push_str(&rules::format_rules(report));
+  }
+
+  if let Some(preview) = &summary.preview {
+    output.push_str(&preview::format_preview(preview));
+  }
+
+  if let Some(grouped) = &summary.group_by {
+    output.push_str(&groups::format_groups(
+      grouped,
+      summary.n_rows,
+      &summary.columns,
+    ));
+    output.push_str("✅ Analysis complete!\n");
+    return output;
+  }
+
+  output.push_str("📋 Column Analysis\n");
+  output.push_str("━━━━━━━━━━━━━━━━━━\n\n");
+
+  for (i, column) in summary.columns.iter().enumerate() {
+    output.push_str(&format!(
+      "{}. Column: '{}' ({})\n",
+      i + 1,
+      column.name,
+      column.data_type
+    ));
+
+    output.push_str(&format_column(column));
+    output.push('\n');
+  }
+
+  output.push_str("✅ Analysis complete!\n");
+
+  output
+}
+
+/// The statistics lines of one column, below its numbered heading.
+fn format_column(column: &ColumnSummary) -> String {
+  let mut output = String::new();
+
+  match (column.null_count, column.null_percentage) {
+    (Some(null_count), Some(percentage)) => match &column.null_percentage_interval {
+      Some(interval) => output.push_str(&format!(
+        "   Nulls: {null_count} ({percentage:.1}%, {}%)\n",
+        sampling::format_interval(interval, 1)
+      )),
+      None => output.push_str(&format!("   Nulls: {null_count} ({percentage:.1}%)\n")),
+    },
+    (Some(null_count), None) => output.push_str(&format!("   Nulls: {null_count}\n")),
+    _ => output.push_str("   Nulls: N/A (not recorded)\n"),
+  }
+
+  match &column.summary {
+    ColumnStats::Numerical {
+      nan_count,
+      infinite_count,
+      min,
+      max,
+      sum,
+      mean,
+      mean_interval,
+      std_dev,
+      median,
+      q25,
+      q75,
+      iqr,
+      percentiles,
+      histogram,
+      decimal,
+      quantile_rank_error,
+    } => {
+      output.push_str("   📈 Numerical Statistics:\n");
+
+      // Decimals print at their declared scale, with exact extremes and total
+      let digits = decimal.as_ref().map_or(6, |decimal| decimal.scale);
+      let stat = |value: Option<f64>| match value {
+        Some(value) => format!("{value:.digits$}"),
+        None => format_stat(None),
+      };
+      let (exact_min, exact_max, exact_sum) = match decimal {
+        Some(decimal) => (
+          decimal.min.clone(),
+          decimal.max.clone(),
+          decimal.sum.clone(),
+        ),
+        None => (None, None, None),
+      };
+
+      if let Some(nan_count) = nan_count {
+        output.push_str(&format!("      NaN: {nan_count}\n"));
+      }
+      if let Some(infinite_count) = infinite_count {
+        output.push_str(&format!("      Infinite: {infinite_count}\n"));
+      }
+
+      output.push_str(&format!(
+        "      Min: {}\n",
+        exact_min.unwrap_or_else(|| stat(*min))
+      ));
+      output.push_str(&format!(
+        "      Max: {}\n",
+        exact_max.unwrap_or_else(|| stat(*max))
+      ));
+      output.push_str(&format!(
+        "      Sum: {}\n",
+        exact_sum.unwrap_or_else(|| stat(*sum))
+      ));
+      match mean_interval {
+        Some(interval) => output.push_str(&format!(
+          "      Mean: {} ({})\n",
+          stat(*mean),
+          sampling::format_interval(interval, digits)
+        )),
+        None => output.push_str(&format!("      Mean: {}\n", stat(*mean))),
+      }
+      output.push_str(&format!("      Std Dev: {}\n", stat(*std_dev)));
+      output.push_str(&format!("      Median: {}\n", stat(*median)));
+
+      match (q25, q75, iqr) {
+        (Some(q25_val), Some(q75_val), Some(iqr_val)) => {
+          output.push_str(&format!("      Q1 (25%): {q25_val:.digits$}\n"));
+          output.push_str(&format!("      Q3 (75%): {q75_val:.digits$}\n"));
+          output.push_str(&format!("      IQR: {iqr_val:.digits$}\n"));
+        }
+        _ => {
+          output.push_str("      Quartiles: N/A (no valid values)\n");
+        }
+      }
+
+      for Percentile { percentile, value } in percentiles {
+        output.push_str(&format!("      P{percentile}: {}\n", stat(*value)));
+      }
+
+      if let Some(error) = quantile_rank_error {
+        output.push_str(&format!(
+          "      Quantiles: KLL sketch estimates (±{error:.2}% rank error)\n"
+        ));
+      }
+
+      if let Some(histogram) = histogram {
+        output.push_str("      Histogram:\n");
+        output.push_str(&histogram::format_histogram(histogram));
+      }
+    }
+
+    ColumnStats::Categorical {
+      frequency_table,
+      total_unique,
+      distinct_error,
+      showing_top_n,
+      frequency_error,
+      strings,
+    } => {
+      if let Some(error) = distinct_error {
+        let estimate =
+          format!("~{total_unique} unique values (HyperLogLog estimate, ±{error:.2}%)");
+        match frequency_error {
+          Some(frequency_error) if !frequency_table.is_empty() => output.push_str(&format!(
+            "   📊 Categorical: {estimate}, top {} by Space-Saving (counts overstate by at most {frequency_error}):\n",
+            frequency_table.len()
+          )),
+          _ => output.push_str(&format!("   📊 Categorical: {estimate}\n")),
+        }
+      } else if frequency_table.is_empty() {
+        output.push_str(&format!(
+          "   📊 Categorical: {total_unique} unique values (too many to display)\n"
+        ));
+      } else if *showing_top_n {
+        output.push_str(&format!(
+          "   📊 Categorical: {total_unique} total unique values (showing top {}):\n",
+          frequency_table.len()
+        ));
+      } else {
+        output.push_str(&format!(
+          "   📊 Categorical: {total_unique} unique values:\n"
+        ));
+      }
+
+      let approximate = if frequency_error.is_some() { "~" } else { "" };
+      for (value, count) in frequency_table {
+        let percentage =
+          (*count as f64 / frequency_table.iter().map(|(_, c)| *c as f64).sum::<f64>()) * 100.0;
+        output.push_str(&format!(
+          "      '{value}': {approximate}{count} ({percentage:.1}%)\n"
+        ));
+      }
+
+      if let Some(strings) = strings {
+        output.push_str(&strings::format_string_stats(strings));
+      }
+    }
+
+    ColumnStats::Boolean {
+      true_count,
+      false_count,
+      true_percentage,
+      false_percentage,
+      true_percentage_interval,
+      false_percentage_interval,
+    } => match (true_percentage, false_percentage) {
+      (Some(true_percentage), Some(false_percentage)) => {
+        let share = |percentage: f64, interval: &Option<ConfidenceInterval>| match interval {
+          Some(interval) => format!(
+            "{percentage:.1}%, {}%",
+            sampling::format_interval(interval, 1)
+          ),
+          None => format!("{percentage:.1}%"),
+        };
+        output.push_str(&format!(
+          "   🔘 Boolean: true {true_count} ({}) · false {false_count} ({})\n",
+          share(*true_percentage, true_percentage_interval),
+          share(*false_percentage, false_percentage_interval)
+        ))
+      }
+      _ => output.push_str(&format!(
+        "   🔘 Boolean: true {true_count} · false {false_count}\n"
+      )),
+    },
+
+    ColumnStats::Temporal {
+      earliest,
+      latest,
+      span,
+      timezone,
+      cadence,
+      gap_count,
+      largest_gap,
+      gaps_through,
+      by_year,
+      by_month,
+      by_weekday,
+    } => {
+      output.push_str("   🕒 Temporal Statistics:\n");
+      output.push_str(&format!(
+        "      Earliest: {}\n",
+        earliest.as_deref().unwrap_or("N/A (no valid values)")
+      ));
+      output.push_str(&format!(
+        "      Latest: {}\n",
+        latest.as_deref().unwrap_or("N/A (no valid values)")
+      ));
+      if let Some(span) = span {
+        output.push_str(&format!("      Span: {span}\n"));
+      }
+      if let Some(timezone) = timezone {
+        output.push_str(&format!("      Timezone: {timezone}\n"));
+      }
+      if let Some(cadence) = cadence {
+        output.push_str(&format!("      Cadence: {cadence}\n"));
+      }
+      let through = gaps_through
+        .as_ref()
+        .map_or(String::new(), |through| format!(" through {through}"));
+      match (gap_count, largest_gap) {
+        (Some(gap_count), Some(largest_gap)) => {
+          output.push_str(&format!(
+            "      Gaps: {gap_count} (largest {largest_gap}){through}\n"
+          ));
+        }
+        (Some(gap_count), None) => output.push_str(&format!("      Gaps: {gap_count}{through}\n")),
+        _ => {}
+      }
+      for (label, distribution) in [
+        ("By year", by_year),
+        ("By month", by_month),
+        ("By weekday", by_weekday),
+      ] {
+        if !distribution.is_empty() {
+          let buckets = distribution
+            .iter()
+            .map(|(bucket, count)| format!("{bucket}: {count}"))
+            .collect::<Vec<_>>();
+          output.push_str(&format!("      {label}: {}\n", buckets.join(", ")));
+        }
+      }
+    }
+
+    ColumnStats::List {
+      min_length,
+      max_length,
+      mean_length,
+      empty_count,
+      empty_percentage,
+      element_count,
+      elements,
+    } => {
+      output.push_str("   📦 List Statistics:\n");
+      match (min_length, mean_length, max_length) {
+        (Some(min_length), Some(mean_length), Some(max_length)) => {
+          output.push_str(&format!(
+            "      Length: min {min_length}, mean {mean_length:.2}, max {max_length}\n"
+          ));
+        }
+        _ => output.push_str("      Length: N/A (no valid values)\n"),
+      }
+      match empty_percentage {
+        Some(percentage) => {
+          output.push_str(&format!("      Empty: {empty_count} ({percentage:.1}%)\n"));
+        }
+        None => output.push_str(&format!("      Empty: {empty_count}\n")),
+      }
+      output.push_str(&format!("      Elements: {element_count}\n"));
+      for element in elements {
+        output.push_str(&format!(
+          "      ↳ '{}' ({})\n",
+          element.name, element.data_type
+        ));
+        for line in format_column(element).lines() {
+          output.push_str(&format!("      {line}\n"));
+        }
+      }
+    }
+
+    ColumnStats::Footer { nested: true, .. } => {
+      output.push_str("   🧾 Footer Statistics: none (nested)\n");
+    }
+    ColumnStats::Footer {
+      min,
+      max,
+      distinct_count,
+      ..
+    } => {
+      output.push_str("   🧾 Footer Statistics:\n");
+      output.push_str(&format!(
+        "      Min: {}\n",
+        min.as_deref().unwrap_or("N/A (not recorded)")
+      ));
+      output.push_str(&format!(
+        "      Max: {}\n",
+        max.as_deref().unwrap_or("N/A (not recorded)")
+      ));
+      if let Some(distinct_count) = distinct_count {
+        output.push_str(&format!("      Distinct: {distinct_count}\n"));
+      }
+    }
+  }
+
+  output
+}
+
+fn format_stat(value: Option<f64>) -> String {
+  match value {
+    Some(value) => format!("{value:.6}"),
+    None => "N/A (no valid values)".to_string(),
+  }
+}
@@ -0,0 +1,511 @@
+//! Declarative data-quality rules (`--rules`), read from a TOML file:
+//!
+//! ```toml
+//! [dataset]
+//! min_rows = 1000
+//!
+//! [columns.id]
+//! unique = true
+//! not_null = true
+//!
+//! [columns.price]
+//! min = 0
+//! max = 10000
+//!
+//! [columns.status]
+//! allowed = ["active", "inactive"]
+//!
+//! [columns.email]
+//! pattern = '[^@\s]+@[^@\s]+'
+//! max_null_percentage = 5.0
+//! ```
+//!
+//! A `pattern` must match the whole value, not just part of it.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::{COUNT_COLUMN, collect_streaming, count_values, percentage};
+
+#[derive(Debug, Serialize)]
+pub struct RulesReport {
+  pub file: String,
+  pub passed: bool,
+  pub results: Vec<RuleResult>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RuleResult {
+  /// `None` for dataset-level rules such as the row count
+  pub column: Option<String>,
+  pub rule: String,
+  pub passed: bool,
+  /// Rows breaking the rule, for rules checked row by row
+  pub failing_rows: Option<u64>,
+  pub message: String,
+}
+
+#[derive(Debug)]
+enum Rule {
+  MinRows(u64),
+  MaxRows(u64),
+  NotNull,
+  Unique,
+  Min(f64),
+  Max(f64),
+  Allowed(Vec<String>),
+  Pattern(String),
+  MaxNullPercentage(f64),
+}
+
+impl Rule {
+  fn describe(&self) -> String {
+    match self {
+      Rule::MinRows(n) => format!("row count ≥ {n}"),
+      Rule::MaxRows(n) => format!("row count ≤ {n}"),
+      Rule::NotNull => "not null".to_string(),
+      Rule::Unique => "unique".to_string(),
+      Rule::Min(min) => format!("≥ {min}"),
+      Rule::Max(max) => format!("≤ {max}"),
+      Rule::Allowed(values) => format!("in [{}]", values.join(", ")),
+      Rule::Pattern(pattern) => format!("matches '{pattern}'"),
+      Rule::MaxNullPercentage(percentage) => format!("null rate ≤ {percentage}%"),
+    }
+  }
+
+  /// Counts the rows breaking a row-by-row rule, as part of one aggregation.
+  /// Nulls never break value rules; `not_null` is there to catch them.
+  fn failing_rows(&self, name: &str) -> Option<Expr> {
+    let column = col(name);
+    let present = column.clone().is_not_null();
+    let failing = match self {
+      Rule::NotNull => column.is_null(),
+      Rule::Min(min) => column.lt(lit(*min)),
+      Rule::Max(max) => column.gt(lit(*max)),
+      Rule::Allowed(values) => {
+        let allowed = Series::new(PlSmallStr::EMPTY, values.as_slice());
+        present.and(
+          column
+            .cast(DataType::String)
+            .is_in(lit(allowed).implode(), false)
+            .not(),
+        )
+      }
+      Rule::Pattern(pattern) => present.and(
+        column
+          .cast(DataType::String)
+          .str()
+          .contains(lit(format!("^(?:{pattern})$")), true)
+          .not(),
+      ),
+      Rule::MinRows(_) | Rule::MaxRows(_) | Rule::Unique | Rule::MaxNullPercentage(_) => {
+        return None;
+      }
+    };
+    Some(failing.sum())
+  }
+}
+
+#[derive(Debug)]
+struct ColumnRules {
+  column: Option<String>,
+  rules: Vec<Rule>,
+}
+
+/// Layout of a rules file. Unknown sections and rules are rejected so a
+/// misspelled rule does not silently pass.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+  #[serde(default)]
+  dataset: DatasetRules,
+  /// Kept as a table so columns are checked in file order
+  #[serde(default)]
+  columns: toml::Table,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DatasetRules {
+  min_rows: Option<u64>,
+  max_rows: Option<u64>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ColumnRulesFile {
+  #[serde(default)]
+  not_null: bool,
+  #[serde(default)]
+  unique: bool,
+  min: Option<f64>,
+  max: Option<f64>,
+  allowed: Option<Vec<String>>,
+  pattern: Option<String>,
+  max_null_percentage: Option<f64>,
+}
+
+/// Parses the rules file into dataset-level and per-column rules.
+fn load_rules(path: &Path) -> Result<Vec<ColumnRules>> {
+  let text = std::fs::read_to_string(path)
+    .with_context(|| format!("Failed to read rules file '{}'", path.display()))?;
+  parse_rules(&text).with_context(|| format!("Invalid rules file '{}'", path.display()))
+}
+
+fn parse_rules(text: &str) -> Result<Vec<ColumnRules>> {
+  let file: RulesFile = toml::from_str(text)?;
+
+  let mut rule_sets = Vec::new();
+  let dataset = [
+    file.dataset.min_rows.map(Rule::MinRows),
+    file.dataset.max_rows.map(Rule::MaxRows),
+  ];
+  let dataset = dataset.into_iter().flatten().collect::<Vec<_>>();
+  if !dataset.is_empty() {
+    rule_sets.push(ColumnRules {
+      column: None,
+      rules: dataset,
+    });
+  }
+
+  for (column, table) in file.columns {
+    let spec = ColumnRulesFile::deserialize(table)
+      .with_context(|| format!("Invalid rules for column '{column}'"))?;
+    if let Some(pattern) = &spec.pattern {
+      Regex::new(pattern).with_context(|| format!("Invalid pattern for column '{column}'"))?;
+    }
+    let rules = [
+      spec.not_null.then_some(Rule::NotNull),
+      spec.unique.then_some(Rule::Unique),
+      spec.min.map(Rule::Min),
+      spec.max.map(Rule::Max),
+      spec.allowed.map(Rule::Allowed),
+      spec.pattern.map(Rule::Pattern),
+      spec.max_null_percentage.map(Rule::MaxNullPercentage),
+    ];
+    rule_sets.push(ColumnRules {
+      column: Some(column),
+      rules: rules.into_iter().flatten().collect(),
+    });
+  }
+
+  Ok(rule_sets)
+}
+
+/// Rejects rules that cannot apply to their column's dtype, naming both,
+/// before any data is read.
+fn validate_rules(rule_sets: &[ColumnRules], schema: &Schema) -> Result<()> {
+  for rule_set in rule_sets {
+    let Some(column) = &rule_set.column else {
+      continue;
+    };
+    let Some(data_type) = schema.get(column) else {
+      continue;
+    };
+    for rule in &rule_set.rules {
+      let name = match rule {
+        Rule::Min(_) => "min",
+        Rule::Max(_) => "max",
+        _ => continue,
+      };
+      if !(data_type.is_primitive_numeric() || data_type.is_decimal()) {
+        anyhow::bail!(
+          "Rule '{name}' on column '{column}' needs a numerical column, but '{column}' is {data_type:?}"
+        );
+      }
+    }
+  }
+  Ok(())
+}
+
+/// Evaluates every rule against the (filtered) dataset. Row-by-row rules
+/// share a single streaming aggregation; uniqueness needs a group-by per
+/// column.
+pub fn check_rules(lazy_frame: &LazyFrame, path: &Path) -> Result<RulesReport> {
+  let rule_sets = load_rules(path)?;
+  let schema = lazy_frame
+    .clone()
+    .collect_schema()
+    .with_context(|| "Failed to read parquet schema")?;
+  validate_rules(&rule_sets, &schema)
+    .with_context(|| format!("Invalid rules file '{}'", path.display()))?;
+
+  let mut exprs = vec![len().alias(COUNT_COLUMN)];
+  for (i, rule_set) in rule_sets.iter().enumerate() {
+    let Some(column) = &rule_set.column else {
+      continue;
+    };
+    if schema.get(column).is_none() {
+      continue;
+    }
+    exprs.push(
+      col(column.as_str())
+        .null_count()
+        .alias(format!("{i}:nulls")),
+    );
+    for (j, rule) in rule_set.rules.iter().enumerate() {
+      if let Some(expr) = rule.failing_rows(column) {
+        exprs.push(expr.alias(format!("{i}:{j}")));
+      }
+    }
+  }
+
+  let counts = collect_streaming(lazy_frame.clone().select(exprs))
+    .with_context(|| "Failed to evaluate rules")?;
+  let count = |name: &str| -> Option<u64> {
+    let value = counts.column(name).ok()?.get(0).ok()?;
+    value.extract::<u64>()
+  };
+  let n_rows = count(COUNT_COLUMN).unwrap_or(0);
+
+  let mut results = Vec::new();
+  for (i, rule_set) in rule_sets.iter().enumerate() {
+    let column = rule_set.column.as_deref();
+    let label = match column {
+      Some(column) => format!("'{column}' "),
+      None => String::new(),
+    };
+
+    if let Some(column) = column
+      && schema.get(column).is_none()
+    {
+      results.push(RuleResult {
+        column: Some(column.to_string()),
+        rule: "exists".to_string(),
+        passed: false,
+        failing_rows: None,
+        message: format!("Column '{column}' not found"),
+      });
+      continue;
+    }
+
+    for (j, rule) in rule_set.rules.iter().enumerate() {
+      let (passed, failing_rows, detail) = match rule {
+        Rule::MinRows(min) => (n_rows >= *min, None, format!("{n_rows} rows")),
+        Rule::MaxRows(max) => (n_rows <= *max, None, format!("{n_rows} rows")),
+        Rule::Unique => {
+          let (values, rows) = duplicates(lazy_frame, column.unwrap_or_default())?;
+          (
+            values == 0,
+            Some(rows),
+            format!("{values} duplicated values in {rows} rows"),
+          )
+        }
+        Rule::MaxNullPercentage(max) => {
+          let nulls = count(&format!("{i}:nulls")).unwrap_or(0);
+          let percentage = percentage(nulls, n_rows as usize).unwrap_or(0.0);
+          (
+            percentage <= *max,
+            Some(nulls),
+            format!("{nulls} nulls ({percentage:.1}%)"),
+          )
+        }
+        _ => {
+          let failing = count(&format!("{i}:{j}")).unwrap_or(0);
+          (
+            failing == 0,
+            Some(failing),
+            format!("{failing} failing rows"),
+          )
+        }
+      };
+
+      results.push(RuleResult {
+        column: column.map(str::to_string),
+        rule: rule.describe(),
+        passed,
+        failing_rows,
+        message: format!("{label}{}: {detail}", rule.describe()),
+      });
+    }
+  }
+
+  Ok(RulesReport {
+    file: path.display().to_string(),
+    passed: results.iter().all(|result| result.passed),
+    results,
+  })
+}
+
+/// Counts the non-null values occurring more than once, and the rows they
+/// occupy.
+fn duplicates(lazy_frame: &LazyFrame, name: &str) -> Result<(u64, u64)> {
+  let duplicates = count_values(lazy_frame, col(name), name)
+    .filter(col(COUNT_COLUMN).gt(lit(1)))
+    .select([len().alias("values"), col(COUNT_COLUMN).sum().alias("rows")]);
+
+  let duplicates = collect_streaming(duplicates)
+    .with_context(|| format!("Failed to check uniqueness of '{name}'"))?;
+  let count = |name: &str| -> Result<u64> {
+    Ok(
+      duplicates
+        .column(name)?
+        .get(0)?
+        .extract::<u64>()
+        .unwrap_or(0),
+    )
+  };
+  Ok((count("values")?, count("rows")?))
+}
+
+pub fn format_rules(report: &RulesReport) -> String {
+  let mut output = String::new();
+
+  let passed = report.results.iter().filter(|result| result.passed).count();
+  output.push_str(&format!(
+    "📐 Data Quality Rules: {passed} of {} passed ({})\n",
+    report.results.len(),
+    report.file
+  ));
+  for result in &report.results {
+    let mark = if result.passed { "✅" } else { "❌" };
+    output.push_str(&format!("   {mark} {}\n", result.message));
+  }
+
+  output.push('\n');
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::TempDir;
+
+  fn describe(rule_sets: &[ColumnRules]) -> Vec<(Option<&str>, Vec<String>)> {
+    rule_sets
+      .iter()
+      .map(|rule_set| {
+        (
+          rule_set.column.as_deref(),
+          rule_set.rules.iter().map(Rule::describe).collect(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn parses_rules_in_file_order() {
+    let rule_sets = parse_rules(
+      r#"
+        # Dataset rules come first
+        [dataset]
+        min_rows = 10
+
+        [columns.price]
+        min = 0
+        max = 9.5
+
+        [columns."payload.status"]
+        allowed = ["active", "inactive"]
+        not_null = true
+
+        [columns.email]
+        pattern = '^[^@\s]+@[^@\s]+$'
+        max_null_percentage = 5
+        unique = false
+      "#,
+    )
+    .unwrap();
+
+    assert_eq!(
+      describe(&rule_sets),
+      vec![
+        (None, vec!["row count ≥ 10".to_string()]),
+        (Some("price"), vec!["≥ 0".to_string(), "≤ 9.5".to_string()]),
+        (
+          Some("payload.status"),
+          vec!["not null".to_string(), "in [active, inactive]".to_string()]
+        ),
+        (
+          Some("email"),
+          vec![
+            r"matches '^[^@\s]+@[^@\s]+$'".to_string(),
+            "null rate ≤ 5%".to_string()
+          ]
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn rejects_unknown_sections_and_rules() {
+    assert!(parse_rules("[table]\nmin_rows = 1").is_err());
+    assert!(parse_rules("[dataset]\nmin = 1").is_err());
+    let error = parse_rules("[columns.id]\nnot_nul = true").unwrap_err();
+    assert!(format!("{error:#}").contains("'id'"), "{error:#}");
+  }
+
+  #[test]
+  fn rejects_malformed_values() {
+    assert!(parse_rules("[dataset]\nmin_rows = -1").is_err());
+    assert!(parse_rules("[columns.id]\nunique = \"yes\"").is_err());
+    assert!(parse_rules("[columns.id]\nallowed = [1, 2]").is_err());
+    assert!(parse_rules("[columns.id]\npattern = '('").is_err());
+    assert!(parse_rules("[columns.id\nunique = true").is_err());
+  }
+
+  #[test]
+  fn rejects_numeric_bounds_on_text_columns() {
+    let rule_sets = parse_rules("[columns.name]\nmin = 0").unwrap();
+    let schema = Schema::from_iter([Field::new("name".into(), DataType::String)]);
+
+    let error = validate_rules(&rule_sets, &schema).unwrap_err().to_string();
+
+    assert!(
+      error.contains("'min'") && error.contains("'name'"),
+      "{error}"
+    );
+  }
+
+  #[test]
+  fn checks_rules_against_the_data() {
+    let frame = df!(
+      "id" => [1, 2, 2, 4],
+      "price" => [Some(1.0), Some(-1.0), None, Some(5.0)],
+    )
+    .unwrap();
+    let root = TempDir::new("rules");
+    let path = root.join("rules.toml");
+    std::fs::write(
+      &path,
+      "[dataset]\nmin_rows = 5\n[columns.id]\nunique = true\n[columns.price]\nmin = 0\nnot_null = true\n[columns.missing]\nunique = true\n",
+    )
+    .unwrap();
+
+    let report = check_rules(&frame.lazy(), &path).unwrap();
+
+    let outcomes = report
+      .results
+      .iter()
+      .map(|result| (result.rule.as_str(), result.passed, result.failing_rows))
+      .collect::<Vec<_>>();
+    assert_eq!(
+      outcomes,
+      vec![
+        ("row count ≥ 5", false, None),
+        ("unique", false, Some(2)),
+        ("not null", false, Some(1)),
+        ("≥ 0", false, Some(1)),
+        ("exists", false, None),
+      ]
+    );
+    assert!(!report.passed);
+  }
+
+  #[test]
+  fn matches_patterns_against_whole_values() {
+    let frame = df!("code" => [Some("AB-12"), Some("xAB-12"), Some("AB-123"), None]).unwrap();
+    let root = TempDir::new("pattern");
+    let path = root.join("rules.toml");
+    std::fs::write(&path, "[columns.code]\npattern = '[A-Z]{2}-\\d{2}|ZZ'\n").unwrap();
+
+    let report = check_rules(&frame.lazy(), &path).unwrap();
+
+    // The alternation is anchored as a whole, so neither branch matches a part
+    let result = &report.results[0];
+    assert_eq!((result.passed, result.failing_rows), (false, Some(2)));
+  }
+}
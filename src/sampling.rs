@@ -0,0 +1,354 @@
+//! Reproducible samples of the rows to profile.
+//!
+//! Rows are drawn uniformly, either a fixed number of them or each row with
+//! a fixed probability, or whole row groups are drawn so that only their
+//! pages are read. The same seed always draws the same sample. Statistics of
+//! a sample are estimates: means and proportions come with confidence
+//! intervals that shrink as the sample covers more of the rows, treating
+//! sampled rows as independent (which understates the spread of row group
+//! samples when similar rows are stored together).
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+use crate::{ColumnStats, ColumnSummary, collect_streaming, metadata};
+
+/// Confidence level of the reported intervals.
+pub const CONFIDENCE_LEVEL: f64 = 0.95;
+
+/// Two-sided standard normal quantile of [`CONFIDENCE_LEVEL`].
+const Z: f64 = 1.959_963_984_540_054;
+
+/// Name of the row index used to pick sampled rows.
+const ROW_INDEX: &str = "__row";
+
+/// How the rows to profile are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "size", rename_all = "snake_case")]
+pub enum Sample {
+  /// Exactly this many rows, or every row of smaller inputs
+  Rows(usize),
+  /// Every row with this probability (0-1)
+  Fraction(f64),
+  /// This many whole row groups, read in file order
+  RowGroups(usize),
+}
+
+impl std::fmt::Display for Sample {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Sample::Rows(rows) => write!(f, "{rows} random rows"),
+      Sample::Fraction(fraction) => write!(f, "random rows with probability {fraction}"),
+      Sample::RowGroups(row_groups) => write!(f, "{row_groups} random row group(s)"),
+    }
+  }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SampleSummary {
+  pub sample: Sample,
+  pub seed: u64,
+  /// Share of the rows that were drawn, used to narrow the intervals
+  pub fraction: f64,
+  pub confidence_level: f64,
+}
+
+/// Range that holds the population value at [`CONFIDENCE_LEVEL`].
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConfidenceInterval {
+  pub lower: f64,
+  pub upper: f64,
+}
+
+/// SplitMix64, fixed so samples only depend on the seed.
+struct Rng(u64);
+
+impl Rng {
+  fn next_u64(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// A uniform value in `0..bound`.
+  fn below(&mut self, bound: u64) -> u64 {
+    ((u128::from(self.next_u64()) * u128::from(bound)) >> 64) as u64
+  }
+
+  /// `count` distinct values of `0..bound`, in ascending order (Floyd's
+  /// algorithm, so memory grows with `count` rather than `bound`).
+  fn choose(&mut self, count: u64, bound: u64) -> Vec<u64> {
+    let mut chosen = HashSet::new();
+    for upper in bound - count..bound {
+      let candidate = self.below(upper + 1);
+      if !chosen.insert(candidate) {
+        chosen.insert(upper);
+      }
+    }
+    let mut chosen = chosen.into_iter().collect::<Vec<_>>();
+    chosen.sort_unstable();
+    chosen
+  }
+}
+
+/// Draws `rows` rows uniformly, returning them with the share of rows drawn.
+/// The rows are counted first so exactly `rows` of them are picked.
+pub(crate) fn sample_rows(
+  lazy_frame: &LazyFrame,
+  rows: usize,
+  seed: u64,
+) -> Result<(LazyFrame, f64)> {
+  let counted = collect_streaming(lazy_frame.clone().select([len()]))
+    .with_context(|| "Failed to count rows to sample")?;
+  let total = counted
+    .get_columns()
+    .first()
+    .and_then(|column| column.get(0).ok())
+    .and_then(|value| value.extract::<u64>())
+    .with_context(|| "Failed to count rows to sample")?;
+  if rows as u64 >= total {
+    return Ok((lazy_frame.clone(), 1.0));
+  }
+
+  let indices = Rng(seed)
+    .choose(rows as u64, total)
+    .into_iter()
+    .map(|index| index as IdxSize)
+    .collect::<Vec<_>>();
+  let indices = Series::new(ROW_INDEX.into(), indices);
+  let sampled = lazy_frame
+    .clone()
+    .with_row_index(ROW_INDEX, None)
+    .filter(col(ROW_INDEX).is_in(lit(indices).implode(), false))
+    .drop([ROW_INDEX]);
+
+  Ok((sampled, rows as f64 / total as f64))
+}
+
+/// Keeps every row with probability `fraction`, deciding each by a seeded
+/// hash of its position so the sample is drawn in one streaming pass.
+pub(crate) fn sample_fraction(lazy_frame: LazyFrame, fraction: f64, seed: u64) -> LazyFrame {
+  let mut rng = Rng(seed);
+  let hash = col(ROW_INDEX).hash(
+    rng.next_u64(),
+    rng.next_u64(),
+    rng.next_u64(),
+    rng.next_u64(),
+  );
+  let threshold = (fraction * u64::MAX as f64) as u64;
+  lazy_frame
+    .with_row_index(ROW_INDEX, None)
+    .filter(hash.lt_eq(lit(threshold)))
+    .drop([ROW_INDEX])
+}
+
+/// Draws `count` row groups uniformly from the files, returning a union of
+/// one scan per row group (sliced to its rows, so the reader skips the rest)
+/// with the share of rows drawn.
+pub(crate) fn sample_row_groups(
+  paths: &[PathBuf],
+  count: usize,
+  seed: u64,
+  scan: impl Fn() -> Result<LazyFrame>,
+) -> Result<(LazyFrame, f64)> {
+  // Offset and length of every row group in the dataset, in scan order
+  let mut row_groups = vec![];
+  let mut offset = 0;
+  for path in paths {
+    for row_group in &metadata::read_file_metadata(path)?.row_groups {
+      row_groups.push((offset, row_group.num_rows()));
+      offset += row_group.num_rows();
+    }
+  }
+  if count >= row_groups.len() {
+    return Ok((scan()?, 1.0));
+  }
+
+  let chosen = Rng(seed).choose(count as u64, row_groups.len() as u64);
+  let sampled_rows = chosen
+    .iter()
+    .map(|index| row_groups[*index as usize].1)
+    .sum::<usize>();
+  // Every slice gets a scan of its own: slices of a shared scan would be
+  // read once in full and cached
+  let slices = chosen
+    .iter()
+    .map(|index| {
+      let (offset, rows) = row_groups[*index as usize];
+      Ok(scan()?.slice(offset as i64, rows as IdxSize))
+    })
+    .collect::<Result<Vec<_>>>()?;
+  let sampled =
+    concat(slices, UnionArgs::default()).with_context(|| "Failed to sample row groups")?;
+
+  Ok((sampled, sampled_rows as f64 / offset.max(1) as f64))
+}
+
+/// Interval of a mean of `count` values, from their standard deviation.
+fn mean_interval(
+  mean: Option<f64>,
+  std_dev: Option<f64>,
+  count: u64,
+  fraction: f64,
+) -> Option<ConfidenceInterval> {
+  let (mean, std_dev) = (mean?, std_dev?);
+  if count < 2 || !mean.is_finite() || !std_dev.is_finite() {
+    return None;
+  }
+  let margin = Z * std_dev / (count as f64).sqrt() * (1.0 - fraction).max(0.0).sqrt();
+  Some(ConfidenceInterval {
+    lower: mean - margin,
+    upper: mean + margin,
+  })
+}
+
+/// Wilson score interval of a proportion of `total` rows, in percent. The
+/// finite population correction enters through the effective sample size.
+fn percentage_interval(count: u64, total: usize, fraction: f64) -> Option<ConfidenceInterval> {
+  if total == 0 {
+    return None;
+  }
+  let share = count as f64 / total as f64;
+  if fraction >= 1.0 {
+    return Some(ConfidenceInterval {
+      lower: share * 100.0,
+      upper: share * 100.0,
+    });
+  }
+  let n = total as f64 / (1.0 - fraction);
+  let z2 = Z * Z;
+  let center = (share + z2 / (2.0 * n)) / (1.0 + z2 / n);
+  let margin = Z * (share * (1.0 - share) / n + z2 / (4.0 * n * n)).sqrt() / (1.0 + z2 / n);
+  Some(ConfidenceInterval {
+    lower: ((center - margin) * 100.0).max(0.0),
+    upper: ((center + margin) * 100.0).min(100.0),
+  })
+}
+
+/// Fills in the confidence intervals of columns summarized from a sample of
+/// `n_rows` rows.
+pub(crate) fn estimate_intervals(columns: &mut [ColumnSummary], n_rows: usize, fraction: f64) {
+  for column in columns {
+    let null_count = column.null_count.unwrap_or(0);
+    column.null_percentage_interval = percentage_interval(null_count, n_rows, fraction);
+
+    match &mut column.summary {
+      ColumnStats::Numerical {
+        nan_count,
+        mean,
+        std_dev,
+        mean_interval: interval,
+        ..
+      } => {
+        let count = (n_rows as u64).saturating_sub(null_count + nan_count.unwrap_or(0));
+        *interval = mean_interval(*mean, *std_dev, count, fraction);
+      }
+      ColumnStats::Boolean {
+        true_count,
+        false_count,
+        true_percentage_interval,
+        false_percentage_interval,
+        ..
+      } => {
+        *true_percentage_interval = percentage_interval(*true_count, n_rows, fraction);
+        *false_percentage_interval = percentage_interval(*false_count, n_rows, fraction);
+      }
+      _ => {}
+    }
+  }
+}
+
+/// Renders an interval as `95% CI lower–upper`, with `digits` decimals.
+pub fn format_interval(interval: &ConfidenceInterval, digits: usize) -> String {
+  format!(
+    "{:.0}% CI {:.digits$}–{:.digits$}",
+    CONFIDENCE_LEVEL * 100.0,
+    interval.lower,
+    interval.upper
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn width(interval: Option<ConfidenceInterval>) -> f64 {
+    let interval = interval.unwrap();
+    interval.upper - interval.lower
+  }
+
+  #[test]
+  fn chooses_distinct_values_below_the_bound() {
+    for (count, bound) in [(0, 10), (1, 1), (5, 10), (10, 10), (100, 1_000_000)] {
+      let chosen = Rng(42).choose(count, bound);
+      assert_eq!(chosen.len() as u64, count);
+      assert!(chosen.windows(2).all(|pair| pair[0] < pair[1]));
+      assert!(chosen.iter().all(|value| *value < bound));
+    }
+  }
+
+  #[test]
+  fn draws_the_same_sample_for_the_same_seed() -> Result<()> {
+    let frame = df!("id" => (0..1000).collect::<Vec<i64>>())?.lazy();
+    let draw = |seed| -> Result<Vec<Option<i64>>> {
+      let (sampled, fraction) = sample_rows(&frame, 50, seed)?;
+      assert_eq!(fraction, 0.05);
+      Ok(sampled.collect()?.column("id")?.i64()?.to_vec())
+    };
+    assert_eq!(draw(7)?, draw(7)?);
+    assert_ne!(draw(7)?, draw(8)?);
+    assert_eq!(draw(7)?.len(), 50);
+
+    let fraction = |seed| -> Result<Vec<Option<i64>>> {
+      let sampled = sample_fraction(frame.clone(), 0.1, seed).collect()?;
+      Ok(sampled.column("id")?.i64()?.to_vec())
+    };
+    assert_eq!(fraction(7)?, fraction(7)?);
+    assert_ne!(fraction(7)?, fraction(8)?);
+    Ok(())
+  }
+
+  #[test]
+  fn keeps_every_row_of_small_inputs() -> Result<()> {
+    let frame = df!("id" => [1i64, 2, 3])?.lazy();
+    let (sampled, fraction) = sample_rows(&frame, 10, 0)?;
+    assert_eq!(fraction, 1.0);
+    assert_eq!(sampled.collect()?.height(), 3);
+    Ok(())
+  }
+
+  #[test]
+  fn intervals_shrink_to_a_point_as_the_sample_covers_the_rows() {
+    let means = [0.1, 0.5, 0.9, 0.99]
+      .map(|fraction| width(mean_interval(Some(10.0), Some(2.0), 1000, fraction)));
+    assert!(means.windows(2).all(|pair| pair[0] > pair[1]), "{means:?}");
+    assert_eq!(width(mean_interval(Some(10.0), Some(2.0), 1000, 1.0)), 0.0);
+
+    let percentages =
+      [0.1, 0.5, 0.9, 0.99].map(|fraction| width(percentage_interval(300, 1000, fraction)));
+    assert!(
+      percentages.windows(2).all(|pair| pair[0] > pair[1]),
+      "{percentages:?}"
+    );
+    assert_eq!(width(percentage_interval(300, 1000, 1.0)), 0.0);
+  }
+
+  #[test]
+  fn intervals_contain_the_estimate() {
+    let interval = mean_interval(Some(10.0), Some(2.0), 100, 0.01).unwrap();
+    // 1.96 standard errors of 0.2, narrowed by the finite population
+    assert!((interval.upper - 10.0 - Z * 0.2 * 0.99f64.sqrt()).abs() < 1e-12);
+    assert!(interval.lower < 10.0 && interval.upper > 10.0);
+
+    let interval = percentage_interval(0, 100, 0.01).unwrap();
+    assert!(interval.lower.abs() < 1e-12);
+    assert!(interval.upper > 0.0 && interval.upper < 5.0);
+    assert!(percentage_interval(0, 0, 0.5).is_none());
+    assert!(mean_interval(Some(1.0), Some(1.0), 1, 0.5).is_none());
+  }
+}
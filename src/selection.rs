@@ -0,0 +1,147 @@
+//! Column selection by exact name or by `^...$` regular expression.
+
+use anyhow::{Context, Result};
+use regex::Regex;
+
+enum Pattern {
+  Name(String),
+  Regex(Regex),
+}
+
+impl Pattern {
+  /// Follows the polars convention: a name wrapped in `^` and `$` is a
+  /// regular expression, anything else is matched literally.
+  fn parse(pattern: &str) -> Result<Self> {
+    if pattern.starts_with('^') && pattern.ends_with('$') {
+      let regex =
+        Regex::new(pattern).with_context(|| format!("Invalid column pattern '{pattern}'"))?;
+      Ok(Pattern::Regex(regex))
+    } else {
+      Ok(Pattern::Name(pattern.to_string()))
+    }
+  }
+
+  fn matches(&self, name: &str) -> bool {
+    match self {
+      // A struct column's name also selects its flattened fields
+      Pattern::Name(expected) => {
+        name == expected
+          || name
+            .strip_prefix(expected.as_str())
+            .is_some_and(|field| field.starts_with('.'))
+      }
+      Pattern::Regex(regex) => regex.is_match(name),
+    }
+  }
+}
+
+/// The columns requested with `--columns` and `--exclude-columns`.
+pub struct ColumnSelection {
+  include: Vec<Pattern>,
+  exclude: Vec<Pattern>,
+}
+
+impl ColumnSelection {
+  pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+    Ok(ColumnSelection {
+      include: include
+        .iter()
+        .map(|p| Pattern::parse(p))
+        .collect::<Result<_>>()?,
+      exclude: exclude
+        .iter()
+        .map(|p| Pattern::parse(p))
+        .collect::<Result<_>>()?,
+    })
+  }
+
+  pub fn is_all(&self) -> bool {
+    self.include.is_empty() && self.exclude.is_empty()
+  }
+
+  /// Filters `names` down to the selected columns, keeping their order.
+  /// Literal names that are not in `names` are rejected, since they are
+  /// almost always typos.
+  pub fn select<'a>(&self, names: &[&'a str]) -> Result<Vec<&'a str>> {
+    for pattern in self.include.iter().chain(&self.exclude) {
+      if let Pattern::Name(name) = pattern
+        && !names.iter().any(|candidate| pattern.matches(candidate))
+      {
+        anyhow::bail!("Column '{name}' not found");
+      }
+    }
+
+    let selected = names
+      .iter()
+      .copied()
+      .filter(|name| {
+        (self.include.is_empty() || self.include.iter().any(|p| p.matches(name)))
+          && !self.exclude.iter().any(|p| p.matches(name))
+      })
+      .collect::<Vec<_>>();
+
+    if selected.is_empty() {
+      anyhow::bail!("No columns left to analyze after applying the column selection");
+    }
+    Ok(selected)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const NAMES: [&str; 6] = [
+    "id",
+    "event_kind",
+    "event_ts",
+    "payload.user_id",
+    "payload.geo.lat",
+    "payload_raw",
+  ];
+
+  fn select(include: &[&str], exclude: &[&str]) -> Result<Vec<&'static str>> {
+    let owned = |patterns: &[&str]| patterns.iter().map(|p| p.to_string()).collect::<Vec<_>>();
+    ColumnSelection::new(&owned(include), &owned(exclude))?.select(&NAMES)
+  }
+
+  #[test]
+  fn keeps_every_column_without_patterns() {
+    assert!(ColumnSelection::new(&[], &[]).unwrap().is_all());
+    assert_eq!(select(&[], &[]).unwrap(), NAMES);
+  }
+
+  #[test]
+  fn selects_names_and_regexes_in_schema_order() {
+    assert_eq!(
+      select(&["^event_.*$", "id"], &[]).unwrap(),
+      ["id", "event_kind", "event_ts"]
+    );
+    assert_eq!(
+      select(&[], &["^event_.*$"]).unwrap(),
+      ["id", "payload.user_id", "payload.geo.lat", "payload_raw"]
+    );
+    assert_eq!(
+      select(&["^event_.*$"], &["event_ts"]).unwrap(),
+      ["event_kind"]
+    );
+  }
+
+  #[test]
+  fn selects_struct_fields_by_their_parent() {
+    assert_eq!(
+      select(&["payload"], &[]).unwrap(),
+      ["payload.user_id", "payload.geo.lat"]
+    );
+    assert_eq!(select(&["payload.geo"], &[]).unwrap(), ["payload.geo.lat"]);
+  }
+
+  #[test]
+  fn rejects_unknown_names_and_empty_selections() {
+    let error = select(&["missing"], &[]).unwrap_err();
+    assert_eq!(error.to_string(), "Column 'missing' not found");
+    assert!(select(&["^nothing$"], &[]).is_err());
+    assert!(select(&["id"], &["id"]).is_err());
+    assert!(select(&["^event_($"], &[]).is_err());
+  }
+}
@@ -0,0 +1,366 @@
+//! Approximate most frequent values with Space-Saving.
+//!
+//! A summary keeps at most `capacity` counters. Each counter overestimates
+//! the true count of its value by at most its `error`, which never exceeds
+//! `n / capacity` for `n` values, and every value without a counter occurs
+//! at most as often as the smallest counter of a full summary. Summaries
+//! merge the standard mergeable way: counters of both are added, a value
+//! missing from one side is credited with that side's minimum, and the
+//! `capacity` largest are kept. This is how batches, row groups, and files
+//! are combined.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use crate::{collect_streaming, sketch_batches};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Counter {
+  count: u64,
+  error: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct SpaceSaving {
+  capacity: usize,
+  counters: HashMap<String, Counter>,
+  total: u64,
+}
+
+/// A value with its estimated count and the most that count can overstate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeavyHitter {
+  pub value: String,
+  pub count: u64,
+  pub error: u64,
+}
+
+impl SpaceSaving {
+  pub fn new(capacity: usize) -> Self {
+    Self {
+      capacity: capacity.max(1),
+      counters: HashMap::new(),
+      total: 0,
+    }
+  }
+
+  /// A summary of exact counts, keeping the `capacity` largest.
+  pub fn from_counts(capacity: usize, counts: HashMap<String, u64>) -> Self {
+    let mut summary = Self::new(capacity);
+    summary.total = counts.values().sum();
+    summary.counters = counts
+      .into_iter()
+      .map(|(value, count)| (value, Counter { count, error: 0 }))
+      .collect();
+    summary.truncate();
+    summary
+  }
+
+  /// Number of values summarized.
+  pub fn total(&self) -> u64 {
+    self.total
+  }
+
+  /// Upper bound on the count of any value without a counter: the smallest
+  /// counter once the summary is full, and zero before, when every value
+  /// seen still has its counter.
+  fn minimum(&self) -> u64 {
+    if self.counters.len() < self.capacity {
+      return 0;
+    }
+    self
+      .counters
+      .values()
+      .map(|counter| counter.count)
+      .min()
+      .unwrap_or(0)
+  }
+
+  /// Folds another summary into this one.
+  pub fn merge(&mut self, other: &SpaceSaving) {
+    let (own_minimum, other_minimum) = (self.minimum(), other.minimum());
+    for (value, counter) in &mut self.counters {
+      if !other.counters.contains_key(value) {
+        counter.count += other_minimum;
+        counter.error += other_minimum;
+      }
+    }
+    for (value, counter) in &other.counters {
+      let merged = self.counters.entry(value.clone()).or_insert(Counter {
+        count: own_minimum,
+        error: own_minimum,
+      });
+      merged.count += counter.count;
+      merged.error += counter.error;
+    }
+    self.total += other.total;
+    self.truncate();
+  }
+
+  /// The `n` values with the largest counts, most frequent first and ties
+  /// broken by value.
+  pub fn top(&self, n: usize) -> Vec<HeavyHitter> {
+    let mut top = self
+      .counters
+      .iter()
+      .map(|(value, counter)| HeavyHitter {
+        value: value.clone(),
+        count: counter.count,
+        error: counter.error,
+      })
+      .collect::<Vec<_>>();
+    top.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    top.truncate(n);
+    top
+  }
+
+  /// Drops all but the `capacity` largest counters. Ties are broken by
+  /// value, so the summary does not depend on hash order.
+  fn truncate(&mut self) {
+    if self.counters.len() <= self.capacity {
+      return;
+    }
+    let mut counters = self.counters.drain().collect::<Vec<_>>();
+    counters.sort_by(|a, b| b.1.count.cmp(&a.1.count).then_with(|| a.0.cmp(&b.0)));
+    counters.truncate(self.capacity);
+    self.counters = counters.into_iter().collect();
+  }
+}
+
+/// Summarizes the non-null values of a column in one streaming pass: every
+/// morsel is counted exactly, cut down to `capacity` counters, and merged.
+pub(crate) fn sketch_column(
+  lazy_frame: &LazyFrame,
+  name: &str,
+  capacity: usize,
+) -> Result<SpaceSaving> {
+  let summary = Arc::new(Mutex::new(SpaceSaving::new(capacity)));
+
+  let batch = move |column: &Column| {
+    let mut counts = HashMap::<String, u64>::new();
+    match column.dtype() {
+      DataType::String => {
+        for value in column.str()?.into_no_null_iter() {
+          *counts.entry(value.to_string()).or_default() += 1;
+        }
+      }
+      _ => {
+        for value in column.as_materialized_series().iter() {
+          let value = match value.get_str() {
+            Some(s) => s.to_string(),
+            None => format!("{value}"),
+          };
+          *counts.entry(value).or_default() += 1;
+        }
+      }
+    }
+    Ok(SpaceSaving::from_counts(capacity, counts))
+  };
+
+  let summarized = lazy_frame
+    .clone()
+    .select([col(name)])
+    .filter(col(name).is_not_null())
+    .select([sketch_batches(
+      col(name),
+      &summary,
+      batch,
+      SpaceSaving::merge,
+    )]);
+  collect_streaming(summarized)
+    .with_context(|| format!("Failed to find the most frequent values of '{name}'"))?;
+
+  let summary = summary
+    .lock()
+    .map_err(|_| anyhow::anyhow!("Heavy hitter summary lock poisoned"))?
+    .clone();
+  Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Exact counts of a chunk of values.
+  fn counts(values: &[String]) -> HashMap<String, u64> {
+    let mut counts = HashMap::new();
+    for value in values {
+      *counts.entry(value.clone()).or_default() += 1;
+    }
+    counts
+  }
+
+  /// Merges one summary per chunk, in order.
+  fn sequential(chunks: &[Vec<String>], capacity: usize) -> SpaceSaving {
+    let mut summary = SpaceSaving::new(capacity);
+    for chunk in chunks {
+      summary.merge(&SpaceSaving::from_counts(capacity, counts(chunk)));
+    }
+    summary
+  }
+
+  /// Merges one summary per chunk pairwise, as a balanced tree.
+  fn tree(chunks: &[Vec<String>], capacity: usize) -> SpaceSaving {
+    let mut summaries = chunks
+      .iter()
+      .map(|chunk| SpaceSaving::from_counts(capacity, counts(chunk)))
+      .collect::<Vec<_>>();
+    while summaries.len() > 1 {
+      summaries = summaries
+        .chunks(2)
+        .map(|pair| {
+          let mut merged = pair[0].clone();
+          if let Some(other) = pair.get(1) {
+            merged.merge(other);
+          }
+          merged
+        })
+        .collect();
+    }
+    summaries
+      .pop()
+      .unwrap_or_else(|| SpaceSaving::new(capacity))
+  }
+
+  /// Checks every guarantee of the summary against the exact counts.
+  fn assert_guarantees(summary: &SpaceSaving, chunks: &[Vec<String>]) {
+    let exact = counts(&chunks.concat());
+    let n = exact.values().sum::<u64>();
+    let bound = n / summary.capacity as u64;
+    assert_eq!(summary.total(), n);
+    assert!(summary.counters.len() <= summary.capacity);
+
+    for (value, truth) in &exact {
+      match summary.counters.get(value) {
+        Some(counter) => {
+          assert!(
+            counter.count >= *truth,
+            "{value}: {counter:?} under {truth}"
+          );
+          assert!(
+            counter.count - counter.error <= *truth,
+            "{value}: {counter:?} vs {truth}"
+          );
+          assert!(
+            counter.error <= bound,
+            "{value}: error {} over {bound}",
+            counter.error
+          );
+        }
+        None => {
+          assert!(*truth <= summary.minimum(), "{value}: {truth} missing");
+          assert!(
+            *truth <= bound,
+            "{value}: {truth} occurrences over {bound} missing"
+          );
+        }
+      }
+    }
+  }
+
+  fn check(chunks: &[Vec<String>], capacity: usize) {
+    assert_guarantees(&sequential(chunks, capacity), chunks);
+    assert_guarantees(&tree(chunks, capacity), chunks);
+    let reversed = chunks.iter().rev().cloned().collect::<Vec<_>>();
+    assert_guarantees(&sequential(&reversed, capacity), &reversed);
+  }
+
+  #[test]
+  fn counts_exactly_within_capacity() {
+    let chunks = vec![
+      vec!["a".into(), "b".into(), "a".into()],
+      vec!["c".into(), "a".into()],
+    ];
+    let top = sequential(&chunks, 3).top(10);
+    assert_eq!(
+      top,
+      vec![
+        HeavyHitter {
+          value: "a".into(),
+          count: 3,
+          error: 0
+        },
+        HeavyHitter {
+          value: "b".into(),
+          count: 1,
+          error: 0
+        },
+        HeavyHitter {
+          value: "c".into(),
+          count: 1,
+          error: 0
+        },
+      ]
+    );
+  }
+
+  #[test]
+  fn finds_a_heavy_value_arriving_after_many_distinct_ones() {
+    // Distinct values fill every summary before the heavy value shows up
+    let mut chunks = (0..20)
+      .map(|chunk| (0..100).map(|i| format!("noise-{chunk}-{i}")).collect())
+      .collect::<Vec<Vec<String>>>();
+    chunks.extend((0..5).map(|_| vec!["heavy".to_string(); 300]));
+    check(&chunks, 10);
+
+    let top = sequential(&chunks, 10).top(1);
+    assert_eq!(top[0].value, "heavy");
+  }
+
+  #[test]
+  fn bounds_values_that_are_evicted_in_turn() {
+    // One more value than counters, each chunk short of a different one, so
+    // every value is dropped from some chunk's summary
+    let capacity = 8;
+    let chunks = (0..=capacity)
+      .map(|skipped| {
+        (0..=capacity)
+          .filter(|value| *value != skipped)
+          .flat_map(|value| vec![format!("v{value}"); 1 + value % 3])
+          .collect()
+      })
+      .collect::<Vec<Vec<String>>>();
+    check(&chunks, capacity);
+  }
+
+  #[test]
+  fn bounds_skewed_streams_merged_in_any_order() {
+    // Zipf-like counts spread across chunks of different sizes
+    let mut state = 7u64;
+    let chunks = (0..40)
+      .map(|chunk| {
+        (0..50 + chunk * 7)
+          .map(|_| {
+            state = state
+              .wrapping_mul(6_364_136_223_846_793_005)
+              .wrapping_add(1);
+            let u = (state >> 11) as f64 / (1u64 << 53) as f64;
+            format!("z{}", (1.0 / (1.0 - u * 0.999)).floor() as u64)
+          })
+          .collect()
+      })
+      .collect::<Vec<Vec<String>>>();
+    for capacity in [1, 5, 20] {
+      check(&chunks, capacity);
+    }
+  }
+
+  #[test]
+  fn sketches_non_null_values_of_a_column() -> Result<()> {
+    let frame = df!("value" => [Some("a"), None, Some("b"), Some("a"), None, Some("a")])?;
+    let summary = sketch_column(&frame.lazy(), "value", 5)?;
+    assert_eq!(summary.total(), 4);
+    let top = summary.top(1);
+    assert_eq!(
+      top,
+      vec![HeavyHitter {
+        value: "a".into(),
+        count: 3,
+        error: 0
+      }]
+    );
+    Ok(())
+  }
+}
@@ -0,0 +1,362 @@
+//! Profiling of text columns: lengths, whitespace, character classes, shape
+//! patterns, and values that look like numbers or dates.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{COUNT_COLUMN, collect_streaming, count_values, percentage, stat_f64, stat_name};
+
+/// Name of the shape mask column when counting patterns.
+const MASK_COLUMN: &str = "__mask";
+
+/// Number of shape patterns reported, most frequent first.
+const TOP_PATTERNS: usize = 10;
+
+/// Longest pattern printed in text output before it is cut short.
+const MAX_PATTERN_WIDTH: usize = 40;
+
+/// An integer, decimal, or scientific-notation number, optionally signed.
+const NUMERIC_LIKE: &str = r"^\s*[+-]?(\d+(\.\d*)?|\.\d+)([eE][+-]?\d+)?\s*$";
+
+/// A year-first or day/month-first date, optionally followed by a time and
+/// a UTC offset.
+const DATE_LIKE: &str = r"^\s*(\d{4}[-/.]\d{1,2}[-/.]\d{1,2}|\d{1,2}[-/.]\d{1,2}[-/.]\d{2,4})([T ]\d{1,2}:\d{2}(:\d{2}(\.\d+)?)?\s*(Z|[+-]\d{2}:?\d{2})?)?\s*$";
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct StringStats {
+  /// Non-null values the statistics below are computed over
+  pub value_count: u64,
+  /// Lengths are in characters, not bytes
+  pub min_length: Option<u64>,
+  pub max_length: Option<u64>,
+  pub mean_length: Option<f64>,
+  pub empty_count: u64,
+  pub whitespace_only_count: u64,
+  pub leading_whitespace_count: u64,
+  pub trailing_whitespace_count: u64,
+  pub non_ascii_count: u64,
+  /// Only present for Binary columns; values that are not valid UTF-8 are
+  /// left out of every other statistic
+  pub invalid_utf8_count: Option<u64>,
+  pub numeric_like_count: u64,
+  pub date_like_count: u64,
+  /// Shape masks with `A` for an uppercase letter, `a` for any other
+  /// letter, and `9` for a digit, most frequent first
+  pub patterns: Vec<(String, u64)>,
+  /// Number of distinct masks, or `None` when patterns were not counted
+  pub total_patterns: Option<usize>,
+}
+
+/// The column as text, or `None` for dtypes that do not hold text.
+fn text(name: &str, data_type: &DataType) -> Option<Expr> {
+  match data_type {
+    DataType::String => Some(col(name)),
+    DataType::Categorical(_, _) | DataType::Enum(_, _) => Some(col(name).cast(DataType::String)),
+    // Casting would fail on the first value that is not valid UTF-8, so
+    // binary values are decoded one by one and invalid ones become null
+    DataType::Binary => Some(col(name).map(decode_utf8, GetOutput::from_type(DataType::String))),
+    _ => None,
+  }
+}
+
+fn decode_utf8(column: Column) -> PolarsResult<Option<Column>> {
+  let decoded: StringChunked = column
+    .binary()?
+    .into_iter()
+    .map(|value| value.and_then(|bytes| std::str::from_utf8(bytes).ok()))
+    .collect();
+  Ok(Some(decoded.with_name(column.name().clone()).into_column()))
+}
+
+pub(crate) fn aggregation_exprs(index: usize, name: &str, data_type: &DataType) -> Vec<Expr> {
+  let Some(text) = text(name, data_type) else {
+    return vec![];
+  };
+  let lengths = text.clone().str().len_chars();
+  let matching = |pattern: &str, stat: &str| {
+    text
+      .clone()
+      .str()
+      .contains(lit(pattern), true)
+      .sum()
+      .alias(stat_name(index, stat))
+  };
+
+  let mut exprs = vec![];
+  // Other dtypes decode every value, so their text count is the `count`
+  // stat; a second identical count breaks the aggregation of empty frames
+  if let DataType::Binary = data_type {
+    exprs.push(text.clone().count().alias(stat_name(index, "text")));
+  }
+  exprs.extend([
+    lengths.clone().min().alias(stat_name(index, "min_length")),
+    lengths.clone().max().alias(stat_name(index, "max_length")),
+    lengths
+      .clone()
+      .mean()
+      .alias(stat_name(index, "mean_length")),
+    lengths
+      .clone()
+      .eq(lit(0))
+      .sum()
+      .alias(stat_name(index, "empty")),
+    // Only ASCII characters are a single byte long
+    text
+      .clone()
+      .str()
+      .len_bytes()
+      .neq(lengths)
+      .sum()
+      .alias(stat_name(index, "non_ascii")),
+    matching(r"^\s+$", "whitespace_only"),
+    matching(r"^\s", "leading_whitespace"),
+    matching(r"\s$", "trailing_whitespace"),
+    matching(NUMERIC_LIKE, "numeric_like"),
+    matching(DATE_LIKE, "date_like"),
+  ]);
+  exprs
+}
+
+pub(crate) fn analyze_string_column(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+  count_patterns: bool,
+) -> Result<Option<StringStats>> {
+  let Some(text) = text(name, data_type) else {
+    return Ok(None);
+  };
+  let count = |stat: &str| stat_f64(stats, index, stat).map_or(0, |count| count as u64);
+  let (value_count, invalid_utf8_count) = match data_type {
+    DataType::Binary => (count("text"), Some(count("count") - count("text"))),
+    _ => (count("count"), None),
+  };
+
+  // Counting masks groups by every distinct one, which is as unbounded as
+  // an exact value count
+  let (patterns, total_patterns) = if count_patterns {
+    let (patterns, total_patterns) = patterns(lazy_frame, text)
+      .with_context(|| format!("Failed to count patterns of '{name}'"))?;
+    (patterns, Some(total_patterns))
+  } else {
+    (vec![], None)
+  };
+
+  Ok(Some(StringStats {
+    value_count,
+    min_length: stat_f64(stats, index, "min_length").map(|length| length as u64),
+    max_length: stat_f64(stats, index, "max_length").map(|length| length as u64),
+    mean_length: stat_f64(stats, index, "mean_length"),
+    empty_count: count("empty"),
+    whitespace_only_count: count("whitespace_only"),
+    leading_whitespace_count: count("leading_whitespace"),
+    trailing_whitespace_count: count("trailing_whitespace"),
+    non_ascii_count: count("non_ascii"),
+    invalid_utf8_count,
+    numeric_like_count: count("numeric_like"),
+    date_like_count: count("date_like"),
+    patterns,
+    total_patterns,
+  }))
+}
+
+/// Counts the shape masks of the values with a streaming group-by, returning
+/// the most frequent ones and the number of distinct masks.
+fn patterns(lazy_frame: &LazyFrame, text: Expr) -> Result<(Vec<(String, u64)>, usize)> {
+  let mask = text
+    .str()
+    .replace_all(lit(r"\p{Nd}"), lit("9"), false)
+    .str()
+    .replace_all(lit(r"[\p{L}--\p{Lu}]"), lit("a"), false)
+    .str()
+    .replace_all(lit(r"\p{Lu}"), lit("A"), false);
+
+  let counts = collect_streaming(count_values(lazy_frame, mask, MASK_COLUMN))?;
+
+  let masks = counts.column(MASK_COLUMN)?.str()?.clone();
+  let mask_counts = counts.column(COUNT_COLUMN)?.cast(&DataType::UInt64)?;
+  let mut patterns = masks
+    .into_iter()
+    .zip(mask_counts.u64()?)
+    .filter_map(|(mask, count)| Some((mask?.to_string(), count?)))
+    .collect::<Vec<_>>();
+
+  // Break ties by pattern for stable output
+  patterns.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+  let total_patterns = patterns.len();
+  patterns.truncate(TOP_PATTERNS);
+
+  Ok((patterns, total_patterns))
+}
+
+/// Renders the string statistics of a column, with shares of its non-null
+/// values.
+pub fn format_string_stats(strings: &StringStats) -> String {
+  let share = |count: u64| match percentage(count, strings.value_count as usize) {
+    Some(percentage) => format!("{count} ({percentage:.1}%)"),
+    None => count.to_string(),
+  };
+  let mut output = String::from("   🔤 String Statistics:\n");
+
+  if let (Some(min), Some(mean), Some(max)) =
+    (strings.min_length, strings.mean_length, strings.max_length)
+  {
+    output.push_str(&format!(
+      "      Length: min {min} · mean {mean:.1} · max {max} characters\n"
+    ));
+  }
+  output.push_str(&format!(
+    "      Empty: {} · whitespace only: {}\n",
+    share(strings.empty_count),
+    share(strings.whitespace_only_count)
+  ));
+  output.push_str(&format!(
+    "      Leading whitespace: {} · trailing whitespace: {}\n",
+    share(strings.leading_whitespace_count),
+    share(strings.trailing_whitespace_count)
+  ));
+  match strings.invalid_utf8_count {
+    Some(invalid) => output.push_str(&format!(
+      "      Non-ASCII: {} · invalid UTF-8: {invalid}\n",
+      share(strings.non_ascii_count)
+    )),
+    None => output.push_str(&format!(
+      "      Non-ASCII: {}\n",
+      share(strings.non_ascii_count)
+    )),
+  }
+  output.push_str(&format!(
+    "      Numeric-like: {} · date-like: {}\n",
+    share(strings.numeric_like_count),
+    share(strings.date_like_count)
+  ));
+
+  if let Some(total_patterns) = strings.total_patterns
+    && !strings.patterns.is_empty()
+  {
+    output.push_str(&format!("      Patterns ({total_patterns} distinct):\n"));
+    for (pattern, count) in &strings.patterns {
+      let pattern = match pattern.char_indices().nth(MAX_PATTERN_WIDTH) {
+        Some((end, _)) => format!("{}…", &pattern[..end]),
+        None => pattern.clone(),
+      };
+      output.push_str(&format!("         '{pattern}': {}\n", share(*count)));
+    }
+  }
+
+  output
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Profiler;
+  use crate::test_support::expect_categorical;
+
+  fn string_stats(frame: &DataFrame, profiler: Profiler) -> StringStats {
+    let summary = profiler.profile_frame(frame).unwrap();
+    expect_categorical!(summary.columns[0], { strings: Some(strings), .. });
+    strings.clone()
+  }
+
+  #[test]
+  fn counts_lengths_whitespace_and_character_classes() {
+    let frame = df!("value" => [
+      Some("AB-12"),
+      Some(" x"),
+      Some("y "),
+      Some(""),
+      Some("   "),
+      Some("3.5"),
+      Some("2024-01-02"),
+      Some("ñandú"),
+      None,
+    ])
+    .unwrap();
+    let strings = string_stats(&frame, Profiler::new());
+
+    assert_eq!(strings.value_count, 8);
+    assert_eq!(
+      (strings.min_length, strings.max_length),
+      (Some(0), Some(10))
+    );
+    assert_eq!(strings.empty_count, 1);
+    assert_eq!(strings.whitespace_only_count, 1);
+    assert_eq!(strings.leading_whitespace_count, 2);
+    assert_eq!(strings.trailing_whitespace_count, 2);
+    // Lengths are in characters, so the accents do not count twice
+    assert_eq!(strings.non_ascii_count, 1);
+    assert_eq!(strings.numeric_like_count, 1);
+    assert_eq!(strings.date_like_count, 1);
+    assert_eq!(strings.invalid_utf8_count, None);
+  }
+
+  #[test]
+  fn ranks_shape_patterns() {
+    let frame = df!("code" => ["AB-12", "CD-34", "xy-56", "Ñu 7", "AB-12"]).unwrap();
+    let strings = string_stats(&frame, Profiler::new());
+
+    assert_eq!(strings.total_patterns, Some(3));
+    assert_eq!(
+      strings.patterns,
+      [
+        ("AA-99".to_string(), 3),
+        ("Aa 9".to_string(), 1),
+        ("aa-99".to_string(), 1)
+      ]
+    );
+  }
+
+  #[test]
+  fn skips_patterns_with_sketches() {
+    let frame = df!("code" => ["AB-12", "CD-34"]).unwrap();
+    for profiler in [
+      Profiler::new().heavy_hitters(10, 100),
+      Profiler::new().approx_distinct(2.0),
+    ] {
+      let strings = string_stats(&frame, profiler);
+
+      assert_eq!(strings.total_patterns, None);
+      assert!(strings.patterns.is_empty());
+      assert!(!format_string_stats(&strings).contains("Patterns"));
+    }
+  }
+
+  #[test]
+  fn counts_invalid_utf8_in_binary_columns() {
+    let values: [&[u8]; 3] = [b"ok", &[0xff, 0xfe], b"fine"];
+    let frame = DataFrame::new(vec![Column::new("payload".into(), values)]).unwrap();
+    let strings = string_stats(&frame, Profiler::new());
+
+    assert_eq!(strings.value_count, 2);
+    assert_eq!(strings.invalid_utf8_count, Some(1));
+    assert_eq!((strings.min_length, strings.max_length), (Some(2), Some(4)));
+  }
+
+  #[test]
+  fn recognizes_numbers_and_dates() {
+    let numeric = regex::Regex::new(NUMERIC_LIKE).unwrap();
+    for value in ["42", " -3.5 ", ".5", "1e-3", "+7."] {
+      assert!(numeric.is_match(value), "{value}");
+    }
+    for value in ["", "1,000", "e5", "3.5.1", "0x1f"] {
+      assert!(!numeric.is_match(value), "{value}");
+    }
+
+    let date = regex::Regex::new(DATE_LIKE).unwrap();
+    for value in [
+      "2024-01-02",
+      "02/01/2024",
+      "2024-01-02T03:04:05Z",
+      "1.2.24 10:30 +0200",
+    ] {
+      assert!(date.is_match(value), "{value}");
+    }
+    for value in ["2024", "Jan 2", "2024-01-02 noon"] {
+      assert!(!date.is_match(value), "{value}");
+    }
+  }
+}
@@ -0,0 +1,386 @@
+//! Profiling of Date, Datetime, Duration, and Time columns.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+
+use crate::{COUNT_COLUMN, ColumnStats, VALUE_COLUMN, collect_streaming, count_values, stat_name};
+
+const STEP_COLUMN: &str = "__step";
+
+/// Most values the cadence and gaps are measured over. The earliest ones
+/// are taken, so the steps between them are exactly those of the column up
+/// to the last of them.
+const CADENCE_VALUES: IdxSize = 100_000;
+
+const MONTHS: [&str; 12] = [
+  "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+/// Aggregate expressions contributed by a temporal column to the statistics plan.
+pub fn aggregation_exprs(index: usize, name: &str) -> Vec<Expr> {
+  vec![
+    col(name).min().alias(stat_name(index, "min")),
+    col(name).max().alias(stat_name(index, "max")),
+  ]
+}
+
+pub fn analyze_temporal_column(
+  lazy_frame: &LazyFrame,
+  stats: &DataFrame,
+  index: usize,
+  name: &str,
+  data_type: &DataType,
+) -> Result<ColumnStats> {
+  let earliest = stat_display(stats, index, "min");
+  let latest = stat_display(stats, index, "max");
+  let tick = tick_nanos(data_type);
+
+  let span = match (
+    stat_physical(stats, index, "min"),
+    stat_physical(stats, index, "max"),
+  ) {
+    (Some(min), Some(max)) => Some(format_span((max as i128 - min as i128) * tick)),
+    _ => None,
+  };
+
+  let timezone = match data_type {
+    DataType::Datetime(_, Some(timezone)) => Some(timezone.to_string()),
+    _ => None,
+  };
+
+  // Cadence and calendar breakdowns only make sense for points in time
+  let is_calendar = matches!(data_type, DataType::Date | DataType::Datetime(_, _));
+
+  let (cadence, gap_count, largest_gap, gaps_through) = if is_calendar {
+    match cadence_gaps(lazy_frame, name)? {
+      Some(gaps) => (
+        Some(format_span(gaps.cadence as i128 * tick)),
+        Some(gaps.gap_count),
+        (gaps.gap_count > 0).then(|| format_span(gaps.largest_gap as i128 * tick)),
+        gaps.through,
+      ),
+      None => (None, None, None, None),
+    }
+  } else {
+    (None, None, None, None)
+  };
+
+  // Calendar fields of timezone-aware values are those of the wall clock in
+  // the column's own zone
+  let (by_year, by_month, by_weekday) = if is_calendar {
+    (
+      distribution(lazy_frame, col(name).dt().year(), |year| year.to_string())?,
+      distribution(lazy_frame, col(name).dt().month(), |month| {
+        label(&MONTHS, month)
+      })?,
+      distribution(lazy_frame, col(name).dt().weekday(), |weekday| {
+        label(&WEEKDAYS, weekday)
+      })?,
+    )
+  } else {
+    (vec![], vec![], vec![])
+  };
+
+  Ok(ColumnStats::Temporal {
+    earliest,
+    latest,
+    span,
+    timezone,
+    cadence,
+    gap_count,
+    largest_gap,
+    gaps_through,
+    by_year,
+    by_month,
+    by_weekday,
+  })
+}
+
+/// Length of one physical unit of the dtype, in nanoseconds.
+fn tick_nanos(data_type: &DataType) -> i128 {
+  match data_type {
+    DataType::Date => 86_400_000_000_000,
+    DataType::Datetime(unit, _) | DataType::Duration(unit) => match unit {
+      TimeUnit::Nanoseconds => 1,
+      TimeUnit::Microseconds => 1_000,
+      TimeUnit::Milliseconds => 1_000_000,
+    },
+    _ => 1,
+  }
+}
+
+fn stat_display(stats: &DataFrame, index: usize, stat: &str) -> Option<String> {
+  let value = stats.column(&stat_name(index, stat)).ok()?.get(0).ok()?;
+  match value {
+    AnyValue::Null => None,
+    value => Some(format!("{value}")),
+  }
+}
+
+fn stat_physical(stats: &DataFrame, index: usize, stat: &str) -> Option<i64> {
+  stats
+    .column(&stat_name(index, stat))
+    .ok()?
+    .to_physical_repr()
+    .get(0)
+    .ok()?
+    .extract::<i64>()
+}
+
+/// Expected step between values and the steps that exceed it, in physical
+/// units.
+struct CadenceGaps {
+  cadence: i64,
+  gap_count: u64,
+  largest_gap: i64,
+  /// Last value measured, when the column has more than [`CADENCE_VALUES`]
+  through: Option<String>,
+}
+
+/// Infers the expected cadence as the most common step between consecutive
+/// distinct values, then counts the steps that exceed it. Only the earliest
+/// [`CADENCE_VALUES`] values are measured, found with a bounded bottom-k
+/// rather than by sorting the column.
+fn cadence_gaps(lazy_frame: &LazyFrame, name: &str) -> Result<Option<CadenceGaps>> {
+  let earliest = lazy_frame
+    .clone()
+    .select([col(name)])
+    .filter(col(name).is_not_null())
+    .bottom_k(
+      CADENCE_VALUES + 1,
+      [col(name)],
+      SortMultipleOptions::default(),
+    );
+  let earliest =
+    collect_streaming(earliest).with_context(|| "Failed to compute temporal cadence")?;
+  let through = if earliest.height() as IdxSize > CADENCE_VALUES {
+    let last = earliest.column(name)?.get(CADENCE_VALUES as usize - 1)?;
+    Some(format!("{last}"))
+  } else {
+    None
+  };
+
+  let value = col(VALUE_COLUMN);
+  let steps = earliest
+    .lazy()
+    .slice(0, CADENCE_VALUES)
+    .select([col(name)
+      .to_physical()
+      .cast(DataType::Int64)
+      .alias(VALUE_COLUMN)])
+    .unique(None, UniqueKeepStrategy::Any)
+    .sort([VALUE_COLUMN], SortMultipleOptions::default())
+    .select([(value.clone() - value.shift(lit(1))).alias(STEP_COLUMN)])
+    .filter(col(STEP_COLUMN).is_not_null())
+    .group_by([col(STEP_COLUMN)])
+    .agg([len().alias(COUNT_COLUMN)]);
+
+  let steps = collect_streaming(steps).with_context(|| "Failed to compute temporal cadence")?;
+  let step_values = steps.column(STEP_COLUMN)?.i64()?.clone();
+  let step_counts = steps.column(COUNT_COLUMN)?.cast(&DataType::UInt64)?;
+  let step_counts = step_counts.u64()?;
+
+  let mut pairs = step_values
+    .into_iter()
+    .zip(step_counts)
+    .filter_map(|(step, count)| Some((step?, count?)))
+    .collect::<Vec<_>>();
+  if pairs.is_empty() {
+    return Ok(None);
+  }
+
+  // Most frequent step wins; ties go to the shorter step
+  pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+  let cadence = pairs[0].0;
+
+  let gap_count = pairs
+    .iter()
+    .filter(|(step, _)| *step > cadence)
+    .map(|(_, count)| count)
+    .sum();
+  let largest_gap = pairs.iter().map(|(step, _)| *step).max().unwrap_or(cadence);
+
+  Ok(Some(CadenceGaps {
+    cadence,
+    gap_count,
+    largest_gap,
+    through,
+  }))
+}
+
+/// Counts non-null values per calendar bucket, ordered by bucket.
+fn distribution(
+  lazy_frame: &LazyFrame,
+  bucket: Expr,
+  label: impl Fn(i64) -> String,
+) -> Result<Vec<(String, u32)>> {
+  let counts = count_values(lazy_frame, bucket.cast(DataType::Int64), VALUE_COLUMN)
+    .sort([VALUE_COLUMN], SortMultipleOptions::default());
+
+  let counts =
+    collect_streaming(counts).with_context(|| "Failed to compute temporal distribution")?;
+  let buckets = counts.column(VALUE_COLUMN)?.i64()?.clone();
+  let bucket_counts = counts.column(COUNT_COLUMN)?.cast(&DataType::UInt32)?;
+
+  Ok(
+    buckets
+      .into_iter()
+      .zip(bucket_counts.u32()?)
+      .filter_map(|(bucket, count)| Some((label(bucket?), count?)))
+      .collect(),
+  )
+}
+
+/// Maps a 1-based calendar number (month, ISO weekday) to its short name.
+fn label(names: &[&str], number: i64) -> String {
+  usize::try_from(number - 1)
+    .ok()
+    .and_then(|i| names.get(i))
+    .map_or_else(|| number.to_string(), |name| name.to_string())
+}
+
+/// Formats a length of time such as `3d 4h 30m` from nanoseconds.
+pub fn format_span(nanos: i128) -> String {
+  const UNITS: [(&str, i128); 4] = [
+    ("d", 86_400_000_000_000),
+    ("h", 3_600_000_000_000),
+    ("m", 60_000_000_000),
+    ("s", 1_000_000_000),
+  ];
+
+  let sign = if nanos < 0 { "-" } else { "" };
+  let mut remaining = nanos.abs();
+  let mut parts = Vec::new();
+  for (unit, size) in UNITS {
+    if remaining >= size {
+      parts.push(format!("{}{unit}", remaining / size));
+      remaining %= size;
+    }
+  }
+
+  if remaining > 0 {
+    let (unit, size) = if remaining % 1_000_000 == 0 {
+      ("ms", 1_000_000)
+    } else if remaining % 1_000 == 0 {
+      ("µs", 1_000)
+    } else {
+      ("ns", 1)
+    };
+    parts.push(format!("{}{unit}", remaining / size));
+  }
+
+  if parts.is_empty() {
+    "0s".to_string()
+  } else {
+    format!("{sign}{}", parts.join(" "))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_support::expect_stats;
+  use crate::{ColumnSummary, Profiler};
+
+  const MINUTE_MS: i64 = 60_000;
+
+  /// Profiles millisecond timestamps in the given zone.
+  fn temporal_column(millis: Vec<i64>, timezone: Option<&str>) -> ColumnSummary {
+    let timezone = TimeZone::opt_try_new(timezone).unwrap();
+    let frame = df!("ts" => millis)
+      .unwrap()
+      .lazy()
+      .select([col("ts").cast(DataType::Datetime(TimeUnit::Milliseconds, timezone))])
+      .collect()
+      .unwrap();
+    let mut summary = Profiler::new().profile_frame(&frame).unwrap();
+    summary.columns.remove(0)
+  }
+
+  #[test]
+  fn finds_the_cadence_and_its_gaps() {
+    // Every minute, with 3 and then 10 minutes missing
+    let minutes = (0..20).chain(23..40).chain(50..60);
+    let column = temporal_column(minutes.map(|minute| minute * MINUTE_MS).collect(), None);
+    expect_stats!(
+      column,
+      Temporal {
+        cadence,
+        gap_count,
+        largest_gap,
+        gaps_through,
+        ..
+      }
+    );
+
+    assert_eq!(cadence.as_deref(), Some("1m"));
+    assert_eq!(*gap_count, Some(2));
+    assert_eq!(largest_gap.as_deref(), Some("11m"));
+    assert_eq!(*gaps_through, None);
+  }
+
+  #[test]
+  fn measures_gaps_over_the_earliest_values_of_large_columns() {
+    // Shuffled, so the earliest values are found without relying on order
+    let count = CADENCE_VALUES as i64 + 10;
+    let minutes = (0..count)
+      .map(|i| (i * 7_919) % count)
+      .filter(|minute| *minute != 5 && *minute < count - 5 || *minute == count - 1);
+    let column = temporal_column(minutes.map(|minute| minute * MINUTE_MS).collect(), None);
+    expect_stats!(
+      column,
+      Temporal {
+        cadence,
+        gap_count,
+        gaps_through,
+        ..
+      }
+    );
+
+    // The missing fifth minute is found, the gap before the last one is not
+    assert_eq!(cadence.as_deref(), Some("1m"));
+    assert_eq!(*gap_count, Some(1));
+    let through = (CADENCE_VALUES as i64) * MINUTE_MS;
+    let expected = AnyValue::Datetime(through, TimeUnit::Milliseconds, None).to_string();
+    assert_eq!(*gaps_through, Some(expected));
+  }
+
+  #[test]
+  fn buckets_timezone_aware_values_in_their_own_zone() {
+    // 2024-01-01 03:00 UTC is Sunday evening, New Year's Eve, in New York
+    let column = temporal_column(vec![1_704_078_000_000], Some("America/New_York"));
+    expect_stats!(
+      column,
+      Temporal {
+        by_year,
+        by_month,
+        by_weekday,
+        ..
+      }
+    );
+
+    assert_eq!(*by_year, vec![("2023".to_string(), 1)]);
+    assert_eq!(*by_month, vec![("Dec".to_string(), 1)]);
+    assert_eq!(*by_weekday, vec![("Sun".to_string(), 1)]);
+  }
+
+  #[test]
+  fn formats_spans_from_days_to_nanoseconds() {
+    assert_eq!(format_span(0), "0s");
+    assert_eq!(format_span(90 * 60 * 1_000_000_000), "1h 30m");
+    assert_eq!(
+      format_span(86_400_000_000_000 + 2_000_000_000 + 5_000_000),
+      "1d 2s 5ms"
+    );
+    assert_eq!(format_span(1_500), "1500ns");
+    assert_eq!(format_span(-2_000), "-2µs");
+  }
+
+  #[test]
+  fn labels_calendar_numbers() {
+    assert_eq!(label(&MONTHS, 1), "Jan");
+    assert_eq!(label(&WEEKDAYS, 7), "Sun");
+    assert_eq!(label(&MONTHS, 13), "13");
+  }
+}
@@ -0,0 +1,68 @@
+//! Helpers shared by the unit tests of every module.
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A fresh directory under the system temp dir, removed with everything in
+/// it when dropped, so files are cleaned up even when a test panics.
+pub(crate) struct TempDir(PathBuf);
+
+impl TempDir {
+  pub(crate) fn new(name: &str) -> Self {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+    let path = std::env::temp_dir().join(format!(
+      "parquet-summarizer-{name}-{}-{}",
+      std::process::id(),
+      NEXT.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::create_dir_all(&path).unwrap();
+    TempDir(path)
+  }
+
+  pub(crate) fn path(&self) -> &Path {
+    &self.0
+  }
+
+  pub(crate) fn join(&self, path: impl AsRef<Path>) -> PathBuf {
+    self.0.join(path)
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
+
+/// Binds the fields of a column's statistics of the given kind, failing the
+/// test with the statistics it got otherwise:
+/// `expect_stats!(column, Temporal { earliest, .. });`
+macro_rules! expect_stats {
+  ($column:expr, $kind:ident { $($fields:tt)* }) => {
+    let column = &$column;
+    let $crate::ColumnStats::$kind { $($fields)* } = &column.summary else {
+      panic!(
+        "expected {} statistics for '{}', got {:?}",
+        stringify!($kind),
+        column.name,
+        column.summary
+      );
+    };
+  };
+}
+
+/// [`expect_stats!`] for numerical statistics.
+macro_rules! expect_numerical {
+  ($column:expr, { $($fields:tt)* }) => {
+    $crate::test_support::expect_stats!($column, Numerical { $($fields)* })
+  };
+}
+
+/// [`expect_stats!`] for categorical statistics.
+macro_rules! expect_categorical {
+  ($column:expr, { $($fields:tt)* }) => {
+    $crate::test_support::expect_stats!($column, Categorical { $($fields)* })
+  };
+}
+
+pub(crate) use {expect_categorical, expect_numerical, expect_stats};